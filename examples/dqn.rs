@@ -0,0 +1,45 @@
+extern crate rsrl;
+
+use rsrl::{run, logging, Parameter, SerialExperiment, Evaluation};
+use rsrl::agents::control::dqn::{DQN, TargetUpdate};
+use rsrl::domains::{Domain, CartPole};
+use rsrl::fa::{MLP, Activation};
+use rsrl::geometry::Space;
+use rsrl::policies::{EpsilonGreedy, Greedy};
+
+
+fn main() {
+    let logger = logging::root(logging::stdout());
+
+    let domain = CartPole::default();
+    let mut agent = {
+        let n_inputs = domain.state_space().dim();
+        let n_actions: usize = domain.action_space().span().into();
+
+        let q_func = MLP::new(n_inputs)
+            .push(64, Activation::ReLU)
+            .push(64, Activation::ReLU)
+            .push(n_actions, Activation::Identity);
+        let policy = EpsilonGreedy::new(Parameter::exponential(1.0, 0.05, 0.99));
+
+        DQN::new(q_func, policy, 0.001, 0.99, 50000, 32)
+            .with_target_update(TargetUpdate::Polyak(0.01))
+            .with_double_q()
+    };
+
+    // Training:
+    let _training_result = {
+        let e = SerialExperiment::new(&mut agent, Box::new(CartPole::default), 1000);
+
+        run(e, 500, Some(logger))
+    };
+
+    // Testing:
+    let testing_result =
+        Evaluation::new(&mut agent, Box::new(CartPole::default), Greedy).next().unwrap();
+
+
+    println!("Solution \u{21D2} {} steps | reward {}",
+             testing_result.steps,
+             testing_result.reward);
+}
@@ -7,7 +7,7 @@ use rsrl::domains::{Domain, Acrobat};
 use rsrl::fa::{Linear, SparseLinear};
 use rsrl::fa::projection::RBFNetwork;
 use rsrl::geometry::Space;
-use rsrl::policies::EpsilonGreedy;
+use rsrl::policies::{EpsilonGreedy, Greedy};
 
 use std::hash::BuildHasherDefault;
 
@@ -39,7 +39,7 @@ fn main() {
 
     // Testing:
     let testing_result =
-        Evaluation::new(&mut agent, Box::new(Acrobat::default)).next().unwrap();
+        Evaluation::new(&mut agent, Box::new(Acrobat::default), Greedy).next().unwrap();
 
 
     println!("Solution \u{21D2} {} steps | reward {}",
@@ -1,6 +1,6 @@
-use Parameter;
+use {Parameter, Clock};
 use agents::{ControlAgent, PredictionAgent};
-use domains::Transition;
+use domains::{Observation, Transition};
 use fa::QFunction;
 use geometry::{Space, ActionSpace};
 use policies::{Policy, Greedy};
@@ -8,6 +8,7 @@ use std::marker::PhantomData;
 
 
 /// Regular gradient descent actor critic.
+#[derive(Serialize, Deserialize)]
 pub struct ActorCritic<S: Space, Q, C, P>
     where Q: QFunction<S>,
           C: PredictionAgent<S>,
@@ -52,12 +53,12 @@ impl<S: Space, Q, C, P> ControlAgent<S, ActionSpace> for ActorCritic<S, Q, C, P>
           C: PredictionAgent<S>,
           P: Policy
 {
-    fn pi(&mut self, s: &S::Repr) -> usize {
-        self.policy.sample(self.actor.evaluate(s).as_slice())
+    fn pi(&mut self, o: &Observation<S, ActionSpace>) -> usize {
+        self.policy.sample_legal(self.actor.evaluate(o.state()).as_slice(), o.actions())
     }
 
-    fn evaluate_policy<T: Policy>(&self, p: &mut T, s: &S::Repr) -> usize {
-        p.sample(self.actor.evaluate(s).as_slice())
+    fn evaluate_policy<T: Policy>(&self, p: &mut T, o: &Observation<S, ActionSpace>) -> usize {
+        p.sample_legal(self.actor.evaluate(o.state()).as_slice(), o.actions())
     }
 
     fn handle_transition(&mut self, t: &Transition<S, ActionSpace>) {
@@ -65,12 +66,16 @@ impl<S: Space, Q, C, P> ControlAgent<S, ActionSpace> for ActorCritic<S, Q, C, P>
 
         let td_error = self.critic.handle_transition(s, ns, t.reward).unwrap();
 
-        self.actor.update_action(s, t.action, self.beta * td_error);
+        self.actor.update_action(s, t.action, self.beta.value() * td_error);
+
+        tick!(Clock::Step => self.beta, self.gamma);
+        tick!(Clock::Update => self.beta, self.gamma);
+        self.policy.handle_update();
+        self.policy.handle_transition();
     }
 
     fn handle_terminal(&mut self, _: &S::Repr) {
-        self.beta = self.beta.step();
-        self.gamma = self.gamma.step();
+        tick!(Clock::Episode => self.beta, self.gamma);
 
         self.policy.handle_terminal();
     }
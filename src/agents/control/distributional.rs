@@ -0,0 +1,156 @@
+use {Parameter, Clock};
+use agents::ControlAgent;
+use domains::{Observation, Transition};
+use fa::{Function, Projection, CategoricalLinear};
+use geometry::{Space, ActionSpace};
+use policies::{Policy, Greedy};
+
+
+/// Categorical distributional Q-learning.
+///
+/// Learns the full distribution of returns under the greedy policy using the
+/// projected distributional Bellman update and a cross-entropy loss.
+///
+/// Bellemare, Marc G., Will Dabney, and Rémi Munos. "A distributional
+/// perspective on reinforcement learning." arXiv preprint arXiv:1707.06887
+/// (2017).
+#[derive(Serialize, Deserialize)]
+pub struct CategoricalQLearning<S: Space, M: Projection<S>, P: Policy> {
+    pub q_func: CategoricalLinear<S, M>,
+    pub policy: P,
+
+    pub alpha: Parameter,
+    pub gamma: Parameter,
+}
+
+impl<S: Space, M: Projection<S>, P: Policy> CategoricalQLearning<S, M, P> {
+    pub fn new<T1, T2>(q_func: CategoricalLinear<S, M>, policy: P, alpha: T1, gamma: T2) -> Self
+        where T1: Into<Parameter>,
+              T2: Into<Parameter>
+    {
+        CategoricalQLearning {
+            q_func: q_func,
+            policy: policy,
+
+            alpha: alpha.into(),
+            gamma: gamma.into(),
+        }
+    }
+}
+
+impl<S: Space, M: Projection<S>, P: Policy> ControlAgent<S, ActionSpace> for CategoricalQLearning<S, M, P> {
+    fn pi(&mut self, o: &Observation<S, ActionSpace>) -> usize {
+        self.policy.sample_legal(self.q_func.evaluate(o.state()).as_slice(), o.actions())
+    }
+
+    fn evaluate_policy<T: Policy>(&self, p: &mut T, o: &Observation<S, ActionSpace>) -> usize {
+        p.sample_legal(self.q_func.evaluate(o.state()).as_slice(), o.actions())
+    }
+
+    fn handle_transition(&mut self, t: &Transition<S, ActionSpace>) {
+        let (s, ns) = (t.from.state(), t.to.state());
+
+        let phi_s = self.q_func.project(s);
+        let phi_ns = self.q_func.project(ns);
+
+        let target = match t.to {
+            Observation::Terminal(_) => {
+                self.q_func.project_target(t.reward, 0.0, &self.q_func.distribution_phi(&phi_ns, 0))
+            },
+            _ => {
+                let nqs = self.q_func.expectations_phi(&phi_ns);
+                let na = Greedy.sample_legal(nqs.as_slice(), t.to.actions());
+
+                self.q_func.project_target(t.reward,
+                                           self.gamma.value(),
+                                           &self.q_func.distribution_phi(&phi_ns, na))
+            },
+        };
+
+        self.q_func.update_distribution_phi(&phi_s, t.action, &target, self.alpha.value());
+
+        tick!(Clock::Step => self.alpha, self.gamma);
+        tick!(Clock::Update => self.alpha, self.gamma);
+        self.policy.handle_update();
+        self.policy.handle_transition();
+    }
+
+    fn handle_terminal(&mut self, _: &S::Repr) {
+        tick!(Clock::Episode => self.alpha, self.gamma);
+
+        self.policy.handle_terminal();
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::CategoricalQLearning;
+    use agents::ControlAgent;
+    use domains::{Observation, Transition};
+    use fa::{Function, CategoricalLinear};
+    use fa::projection::UniformGrid;
+    use geometry::{RegularSpace, ActionSpace};
+    use geometry::dimensions::{Continuous, Partitioned};
+    use policies::Greedy;
+
+    type Agent = CategoricalQLearning<RegularSpace<Continuous>, UniformGrid, Greedy>;
+
+    fn agent(gamma: f64) -> Agent {
+        let grid = UniformGrid::new(RegularSpace::new().push(Partitioned::new(0.0, 1.0, 2)));
+
+        CategoricalQLearning::new(CategoricalLinear::new(grid, 2, 5, -1.0, 1.0), Greedy, 0.5, gamma)
+    }
+
+    fn full(x: f64) -> Observation<RegularSpace<Continuous>, ActionSpace> {
+        Observation::Full {
+            state: vec![x],
+            actions: vec![0, 1],
+        }
+    }
+
+    fn transition(from: f64, action: usize, reward: f64, to: Option<f64>)
+                  -> Transition<RegularSpace<Continuous>, ActionSpace> {
+        Transition {
+            from: full(from),
+            action: action,
+            reward: reward,
+            to: match to {
+                Some(x) => full(x),
+                None => Observation::Terminal(vec![from]),
+            },
+        }
+    }
+
+    #[test]
+    fn test_terminal_targets() {
+        let mut agent = agent(0.9);
+
+        for i in 0..2000 {
+            let a = i % 2;
+
+            agent.handle_transition(&transition(0.25, a, if a == 0 { 1.0 } else { -1.0 }, None));
+        }
+
+        let qs = agent.q_func.evaluate(&vec![0.25]);
+
+        assert!((qs[0] - 1.0).abs() < 0.05);
+        assert!((qs[1] + 1.0).abs() < 0.05);
+        assert_eq!(agent.pi(&full(0.25)), 0);
+    }
+
+    #[test]
+    fn test_bootstrap() {
+        let mut agent = agent(0.5);
+
+        for _ in 0..2000 {
+            agent.handle_transition(&transition(0.75, 0, 1.0, None));
+            agent.handle_transition(&transition(0.25, 0, 0.0, Some(0.75)));
+        }
+
+        let dist = agent.q_func.distribution(&vec![0.25], 0);
+
+        assert!(dist[3] > 0.95);
+        assert!((agent.q_func.evaluate(&vec![0.25])[0] - 0.5).abs() < 0.05);
+    }
+}
@@ -0,0 +1,349 @@
+use {Parameter, Clock};
+use agents::ControlAgent;
+use agents::memory::ReplayBuffer;
+use domains::{Observation, Transition};
+use fa::MLP;
+use geometry::{RegularSpace, ActionSpace};
+use geometry::dimensions::Continuous;
+use ndarray::Array1;
+use policies::{Policy, Greedy};
+
+
+/// A single stored transition.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Experience {
+    pub state: Vec<f64>,
+    pub action: usize,
+    pub reward: f64,
+    pub next_state: Vec<f64>,
+    pub next_actions: Vec<usize>,
+    pub terminal: bool,
+}
+
+
+/// Strategy used to keep the target network in sync with the online network.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum TargetUpdate {
+    /// Copy the online network every `n` updates.
+    Periodic(usize),
+
+    /// Move the target network a fraction `tau` towards the online network
+    /// after every update.
+    Polyak(f64),
+}
+
+
+/// Deep Q-network with experience replay and a target network.
+///
+/// Temporal difference errors are clipped to `[-huber, huber]`, which
+/// corresponds to minimising the Huber loss. If `double` is set, the greedy
+/// next action is selected with the online network and evaluated with the
+/// target network.
+///
+/// Mnih, Volodymyr, et al. "Human-level control through deep reinforcement
+/// learning." Nature 518.7540 (2015): 529-533.
+///
+/// Van Hasselt, Hado, Arthur Guez, and David Silver. "Deep Reinforcement
+/// Learning with Double Q-Learning." AAAI. Vol. 16. 2016.
+#[derive(Serialize, Deserialize)]
+pub struct DQN<P: Policy> {
+    pub q_func: MLP,
+    pub target: MLP,
+    pub policy: P,
+
+    pub alpha: Parameter,
+    pub gamma: Parameter,
+
+    pub batch_size: usize,
+    pub target_update: TargetUpdate,
+    pub huber: f64,
+    pub double: bool,
+
+    replay: ReplayBuffer<Experience>,
+    n_updates: usize,
+}
+
+impl<P: Policy> DQN<P> {
+    pub fn new<T1, T2>(q_func: MLP,
+                       policy: P,
+                       alpha: T1,
+                       gamma: T2,
+                       capacity: usize,
+                       batch_size: usize)
+                       -> Self
+        where T1: Into<Parameter>,
+              T2: Into<Parameter>
+    {
+        DQN {
+            target: q_func.clone(),
+            q_func: q_func,
+            policy: policy,
+
+            alpha: alpha.into(),
+            gamma: gamma.into(),
+
+            batch_size: batch_size,
+            target_update: TargetUpdate::Periodic(1000),
+            huber: 1.0,
+            double: false,
+
+            replay: ReplayBuffer::new(capacity),
+            n_updates: 0,
+        }
+    }
+
+    pub fn with_target_update(mut self, target_update: TargetUpdate) -> Self {
+        self.target_update = target_update;
+        self
+    }
+
+    pub fn with_huber(mut self, huber: f64) -> Self {
+        self.huber = huber;
+        self
+    }
+
+    pub fn with_double_q(mut self) -> Self {
+        self.double = true;
+        self
+    }
+
+    fn bootstrap(&self, ns: &[f64], legal: &[usize]) -> f64 {
+        let tqs = self.target.predict(ns);
+
+        let na = if self.double {
+            Greedy.sample_legal(self.q_func.predict(ns).as_slice().unwrap(), legal)
+        } else {
+            Greedy.sample_legal(tqs.as_slice().unwrap(), legal)
+        };
+
+        tqs[na]
+    }
+
+    fn learn(&mut self) {
+        let batch: Vec<Experience> =
+            self.replay.sample(self.batch_size).into_iter().cloned().collect();
+
+        let gamma = self.gamma.value();
+        let scale = self.alpha.value() / self.batch_size as f64;
+
+        let mut grads: Option<Vec<(_, _)>> = None;
+
+        for e in batch.iter() {
+            let target = if e.terminal {
+                e.reward
+            } else {
+                e.reward + gamma * self.bootstrap(&e.next_state, &e.next_actions)
+            };
+
+            let td_error = target - self.q_func.predict(&e.state)[e.action];
+
+            let mut errors = Array1::zeros((self.q_func.n_outputs(),));
+            errors[e.action] = scale * clip!(-self.huber, td_error, self.huber);
+
+            let g = self.q_func.gradients(&e.state, &errors);
+
+            grads = Some(match grads {
+                None => g,
+                Some(mut acc) => {
+                    for (&mut (ref mut aw, ref mut ab), (gw, gb)) in acc.iter_mut().zip(g) {
+                        *aw += &gw;
+                        *ab += &gb;
+                    }
+
+                    acc
+                },
+            });
+        }
+
+        if let Some(grads) = grads {
+            for (l, (gw, gb)) in self.q_func.layers.iter_mut().zip(grads) {
+                l.weights += &gw;
+                l.bias += &gb;
+            }
+        }
+
+        self.n_updates += 1;
+        tick!(Clock::Update => self.alpha, self.gamma);
+        self.policy.handle_update();
+
+        match self.target_update {
+            TargetUpdate::Periodic(n) => if self.n_updates % n == 0 {
+                self.target = self.q_func.clone();
+            },
+            TargetUpdate::Polyak(tau) => self.target.soft_update(&self.q_func, tau),
+        }
+    }
+}
+
+impl<P: Policy> ControlAgent<RegularSpace<Continuous>, ActionSpace> for DQN<P> {
+    fn pi(&mut self, o: &Observation<RegularSpace<Continuous>, ActionSpace>) -> usize {
+        let qs = self.q_func.predict(o.state());
+
+        self.policy.sample_legal(qs.as_slice().unwrap(), o.actions())
+    }
+
+    fn evaluate_policy<T: Policy>(&self,
+                                  p: &mut T,
+                                  o: &Observation<RegularSpace<Continuous>, ActionSpace>)
+                                  -> usize {
+        let qs = self.q_func.predict(o.state());
+
+        p.sample_legal(qs.as_slice().unwrap(), o.actions())
+    }
+
+    fn handle_transition(&mut self, t: &Transition<RegularSpace<Continuous>, ActionSpace>) {
+        let terminal = match t.to {
+            Observation::Terminal(_) => true,
+            _ => false,
+        };
+
+        self.replay.push(Experience {
+            state: t.from.state().clone(),
+            action: t.action,
+            reward: t.reward,
+            next_state: t.to.state().clone(),
+            next_actions: t.to.actions().to_vec(),
+            terminal: terminal,
+        });
+
+        if self.replay.len() >= self.batch_size {
+            self.learn();
+        }
+
+        tick!(Clock::Step => self.alpha, self.gamma);
+        self.policy.handle_transition();
+    }
+
+    fn handle_terminal(&mut self, _: &Vec<f64>) {
+        tick!(Clock::Episode => self.alpha, self.gamma);
+
+        self.policy.handle_terminal();
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{DQN, TargetUpdate};
+    use agents::ControlAgent;
+    use domains::{Observation, Transition};
+    use fa::{MLP, Activation};
+    use geometry::{RegularSpace, ActionSpace};
+    use geometry::dimensions::Continuous;
+    use ndarray::{Array2, arr1};
+    use policies::Greedy;
+
+    fn linear(weights: &[f64]) -> MLP {
+        let mut net = MLP::new(1).push(weights.len(), Activation::Identity);
+        net.layers[0].weights =
+            Array2::from_shape_vec((1, weights.len()), weights.to_vec()).unwrap();
+
+        net
+    }
+
+    fn agent(online: &[f64], target: &[f64]) -> DQN<Greedy> {
+        let mut agent = DQN::new(linear(online), Greedy, 0.5, 0.5, 1, 1).with_huber(10.0);
+        agent.target = linear(target);
+
+        agent
+    }
+
+    fn transition(from: f64, action: usize, reward: f64, to: Option<(f64, Vec<usize>)>)
+                  -> Transition<RegularSpace<Continuous>, ActionSpace> {
+        Transition {
+            from: Observation::Full {
+                state: vec![from],
+                actions: vec![],
+            },
+            action: action,
+            reward: reward,
+            to: match to {
+                Some((x, actions)) => Observation::Full {
+                    state: vec![x],
+                    actions: actions,
+                },
+                None => Observation::Terminal(vec![from]),
+            },
+        }
+    }
+
+    #[test]
+    fn test_bootstrap() {
+        let agent = agent(&[5.0, 0.0, 1.0], &[1.0, 3.0, 2.0]);
+
+        assert_eq!(agent.bootstrap(&[1.0], &[]), 3.0);
+        assert_eq!(agent.bootstrap(&[1.0], &[0, 2]), 2.0);
+    }
+
+    #[test]
+    fn test_double_q() {
+        let agent = agent(&[5.0, 0.0, 1.0], &[1.0, 3.0, 2.0]).with_double_q();
+
+        // The online network selects the action, the target network evaluates it:
+        assert_eq!(agent.bootstrap(&[1.0], &[]), 1.0);
+        assert_eq!(agent.bootstrap(&[1.0], &[1, 2]), 2.0);
+    }
+
+    #[test]
+    fn test_terminal_target() {
+        let mut agent = agent(&[0.0, 0.0], &[100.0, 100.0]);
+
+        agent.handle_transition(&transition(0.0, 1, 1.0, None));
+
+        assert!(agent.q_func.predict(&[0.0]).all_close(&arr1(&[0.0, 0.5]), 1e-7));
+    }
+
+    #[test]
+    fn test_learn() {
+        let mut agent = agent(&[0.0, 0.0, 0.0], &[1.0, 3.0, 2.0]);
+
+        // Target is 0.0 + 0.5 * 3.0, and only the bias of action 0 sees the input:
+        agent.handle_transition(&transition(0.0, 0, 0.0, Some((1.0, vec![0, 1, 2]))));
+        assert!(agent.q_func.predict(&[0.0]).all_close(&arr1(&[0.75, 0.0, 0.0]), 1e-7));
+
+        // The error, now 0.75, is clipped by the Huber threshold:
+        agent.huber = 0.5;
+        agent.learn();
+        assert!(agent.q_func.predict(&[0.0]).all_close(&arr1(&[1.0, 0.0, 0.0]), 1e-7));
+    }
+
+    #[test]
+    fn test_periodic_sync() {
+        let mut agent = agent(&[0.0, 0.0], &[1.0, 1.0])
+            .with_target_update(TargetUpdate::Periodic(2));
+
+        agent.handle_transition(&transition(0.0, 0, 1.0, None));
+
+        assert!(agent.target.predict(&[1.0]).all_close(&arr1(&[1.0, 1.0]), 1e-7));
+
+        agent.handle_transition(&transition(0.0, 0, 1.0, None));
+
+        assert!(agent.target.predict(&[1.0]).all_close(&agent.q_func.predict(&[1.0]), 1e-7));
+        assert!(agent.target.predict(&[1.0]).all_close(&arr1(&[0.75, 0.0]), 1e-7));
+    }
+
+    #[test]
+    fn test_polyak_sync() {
+        let mut agent = agent(&[0.0, 0.0], &[2.0, 2.0])
+            .with_target_update(TargetUpdate::Polyak(0.5));
+
+        agent.handle_transition(&transition(0.0, 0, 1.0, None));
+
+        // Online: w = [0, 0], b = [0.5, 0]; target: w = [2, 2], b = [0, 0].
+        assert!(agent.target.predict(&[0.0]).all_close(&arr1(&[0.25, 0.0]), 1e-7));
+        assert!(agent.target.predict(&[1.0]).all_close(&arr1(&[1.25, 1.0]), 1e-7));
+    }
+
+    #[test]
+    fn test_legal_target() {
+        let mut single = agent(&[0.0, 0.0, 0.0], &[1.0, 3.0, 2.0]);
+        let mut double = agent(&[0.0, 3.0, 1.0], &[1.0, 3.0, 2.0]).with_double_q();
+
+        // Action 1 has the largest value under both networks, but is illegal:
+        single.handle_transition(&transition(0.0, 0, 0.0, Some((1.0, vec![0, 2]))));
+        double.handle_transition(&transition(0.0, 0, 0.0, Some((1.0, vec![0, 2]))));
+
+        assert!(single.q_func.predict(&[0.0]).all_close(&arr1(&[0.5, 0.0, 0.0]), 1e-7));
+        assert!(double.q_func.predict(&[0.0]).all_close(&arr1(&[0.5, 0.0, 0.0]), 1e-7));
+    }
+}
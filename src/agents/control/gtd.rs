@@ -1,8 +1,9 @@
-use Parameter;
-use agents::ControlAgent;
-use domains::Transition;
-use fa::{Function, VFunction, QFunction, Projection, Linear};
+use {Parameter, Clock};
+use agents::{ControlAgent, StepSize};
+use domains::{Observation, Transition};
+use fa::LinearQFunction;
 use geometry::{Space, ActionSpace};
+use ndarray::Array1;
 use policies::{Policy, Greedy};
 use std::marker::PhantomData;
 
@@ -11,37 +12,35 @@ use std::marker::PhantomData;
 ///
 /// Maei, Hamid R., et al. "Toward off-policy learning control with function approximation."
 /// Proceedings of the 27th International Conference on Machine Learning (ICML-10). 2010.
-pub struct GreedyGQ<S: Space, M: Projection<S>, P: Policy> {
-    pub q_func: Linear<S, M>,
-    pub v_func: Linear<S, M>,
-
+#[derive(Serialize, Deserialize)]
+pub struct GreedyGQ<S: Space, Q: LinearQFunction<S>, P: Policy> {
+    pub q_func: Q,
     pub policy: P,
 
-    pub alpha: Parameter,
+    /// Auxiliary weights estimating the expected TD error given φ(s, a).
+    pub w: Array1<f64>,
+
+    pub alpha: StepSize,
     pub beta: Parameter,
     pub gamma: Parameter,
 
     phantom: PhantomData<S>,
 }
 
-impl<S: Space, M: Projection<S>, P: Policy> GreedyGQ<S, M, P> {
-    pub fn new<T1, T2, T3>(q_func: Linear<S, M>,
-                           v_func: Linear<S, M>,
-                           policy: P,
-                           alpha: T1,
-                           beta: T2,
-                           gamma: T3)
-                           -> Self
-        where T1: Into<Parameter>,
+impl<S: Space, Q: LinearQFunction<S>, P: Policy> GreedyGQ<S, Q, P> {
+    pub fn new<T1, T2, T3>(q_func: Q, policy: P, alpha: T1, beta: T2, gamma: T3) -> Self
+        where T1: Into<StepSize>,
               T2: Into<Parameter>,
               T3: Into<Parameter>
     {
+        let n_features = q_func.n_features();
+
         GreedyGQ {
             q_func: q_func,
-            v_func: v_func,
-
             policy: policy,
 
+            w: Array1::zeros((n_features,)),
+
             alpha: alpha.into(),
             beta: beta.into(),
             gamma: gamma.into(),
@@ -51,42 +50,99 @@ impl<S: Space, M: Projection<S>, P: Policy> GreedyGQ<S, M, P> {
     }
 }
 
-impl<S: Space, M: Projection<S>, P: Policy> ControlAgent<S, ActionSpace> for GreedyGQ<S, M, P> {
-    fn pi(&mut self, s: &S::Repr) -> usize {
-        let qs: Vec<f64> = self.q_func.evaluate(s);
-
-        self.policy.sample(qs.as_slice())
+impl<S: Space, Q, P> ControlAgent<S, ActionSpace> for GreedyGQ<S, Q, P>
+    where Q: LinearQFunction<S>,
+          P: Policy
+{
+    fn pi(&mut self, o: &Observation<S, ActionSpace>) -> usize {
+        self.policy.sample_legal(self.q_func.evaluate(o.state()).as_slice(), o.actions())
     }
 
-    fn evaluate_policy<T: Policy>(&self, p: &mut T, s: &S::Repr) -> usize {
-        let qs: Vec<f64> = self.q_func.evaluate(s);
-
-        p.sample(qs.as_slice())
+    fn evaluate_policy<T: Policy>(&self, p: &mut T, o: &Observation<S, ActionSpace>) -> usize {
+        p.sample_legal(self.q_func.evaluate(o.state()).as_slice(), o.actions())
     }
 
     fn handle_transition(&mut self, t: &Transition<S, ActionSpace>) {
-        let a = t.action;
         let (s, ns) = (t.from.state(), t.to.state());
 
-        let phi_s = self.q_func.project(s);
-        let phi_ns = self.q_func.project(ns);
+        let na = Greedy.sample_legal(self.q_func.evaluate(ns).as_slice(), t.to.actions());
+
+        let phi_s = self.q_func.phi_action(s, t.action);
+        let phi_ns = self.q_func.phi_action(ns, na);
 
-        let td_error = t.reward +
-                       self.q_func.evaluate_action_phi(&(self.gamma.value() * &phi_ns - &phi_s), a);
-        let td_estimate: f64 = self.v_func.evaluate(s);
+        let td_error = t.reward + self.gamma.value() * self.q_func.evaluate_features(&phi_ns) -
+                       self.q_func.evaluate_features(&phi_s);
+        let td_estimate = self.w.dot(&phi_s);
 
-        let update_q = td_error * &phi_s - self.gamma * td_estimate * phi_ns;
-        let update_v = (td_error - td_estimate) * phi_s;
+        // Step sizes are adapted on the TD error alone; the gradient correction
+        // is then applied with the same per-feature step sizes:
+        let dphi = self.gamma.value() * &phi_ns - &phi_s;
+        let increment = self.alpha.increment(&phi_s, &phi_s, &dphi, td_error);
+        let correction = self.alpha.scale(&(self.gamma.value() * td_estimate * phi_ns));
 
-        self.q_func.update_action_phi(&update_q, a, self.alpha.value());
-        VFunction::update_phi(&mut self.v_func, &update_v, self.alpha * self.beta);
+        self.q_func.update_features(&(increment - correction), 1.0);
+        self.w += &(self.beta.value() * (td_error - td_estimate) * self.alpha.scale(&phi_s));
+
+        self.alpha.tick(Clock::Step);
+        self.alpha.tick(Clock::Update);
+        tick!(Clock::Step => self.beta, self.gamma);
+        tick!(Clock::Update => self.beta, self.gamma);
+        self.policy.handle_update();
+        self.policy.handle_transition();
     }
 
     fn handle_terminal(&mut self, _: &S::Repr) {
-        self.alpha = self.alpha.step();
-        self.beta = self.beta.step();
-        self.gamma = self.gamma.step();
+        self.alpha.tick(Clock::Episode);
+        tick!(Clock::Episode => self.beta, self.gamma);
 
         self.policy.handle_terminal();
     }
 }
+
+
+#[cfg(test)]
+mod tests {
+    use super::GreedyGQ;
+    use agents::{ControlAgent, StepSize};
+    use domains::{Observation, Transition};
+    use fa::{Function, Linear};
+    use fa::projection::UniformGrid;
+    use geometry::RegularSpace;
+    use geometry::dimensions::Partitioned;
+    use ndarray::arr1;
+    use policies::Greedy;
+
+    #[test]
+    fn test_step_size_adaptation() {
+        let grid = UniformGrid::new(RegularSpace::new().push(Partitioned::new(0.0, 1.0, 2)));
+        let alpha = StepSize::idbd(0.01, 0.01);
+        let mut agent = GreedyGQ::new(Linear::new(grid, 2), Greedy, alpha, 0.1, 0.0);
+
+        // With gamma = 0 the correction term vanishes, so the step size must
+        // follow IDBD on the TD error of the LMS rule:
+        let mut reference = StepSize::idbd(0.01, 0.01);
+        let (phi, dphi) = (arr1(&[1.0]), arr1(&[-1.0]));
+        let mut w = 0.0;
+
+        for _ in 0..200 {
+            agent.handle_transition(&Transition {
+                from: Observation::Full {
+                    state: vec![0.25],
+                    actions: vec![0, 1],
+                },
+                action: 0,
+                reward: 1.0,
+                to: Observation::Full {
+                    state: vec![0.75],
+                    actions: vec![0, 1],
+                },
+            });
+            w += reference.increment(&phi, &phi, &dphi, 1.0 - w)[0];
+        }
+
+        let qs: Vec<f64> = agent.q_func.evaluate(&vec![0.25]);
+
+        assert!((qs[0] - w).abs() < 1e-12);
+        assert!(reference.value() > 0.01);
+    }
+}
@@ -1,10 +1,18 @@
-use domains::Transition;
+use domains::{Observation, Transition};
 use geometry::Space;
 use policies::Policy;
 
 pub trait ControlAgent<S: Space, A: Space> {
-    fn pi(&mut self, s: &S::Repr) -> usize;
-    fn evaluate_policy<T: Policy>(&self, p: &mut T, s: &S::Repr) -> usize;
+    /// Sample an action from the agent's (behaviour) policy, restricted to the
+    /// legal actions of the observation.
+    ///
+    /// An empty `o.actions()`, as returned for terminal observations, means
+    /// that every action is legal.
+    fn pi(&mut self, o: &Observation<S, A>) -> A::Repr;
+
+    /// Sample an action from the policy `p`, with the same legality rules as
+    /// `pi`.
+    fn evaluate_policy<T: Policy<A>>(&self, p: &mut T, o: &Observation<S, A>) -> A::Repr;
 
     fn handle_transition(&mut self, t: &Transition<S, A>);
     fn handle_terminal(&mut self, s: &S::Repr);
@@ -14,3 +22,5 @@ pub trait ControlAgent<S: Space, A: Space> {
 pub mod td;
 pub mod gtd;
 pub mod actor_critic;
+pub mod distributional;
+pub mod dqn;
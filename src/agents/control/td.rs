@@ -1,18 +1,21 @@
-use Parameter;
-use agents::ControlAgent;
-use domains::Transition;
-use fa::QFunction;
+use {Parameter, Clock};
+use agents::{ControlAgent, StepSize};
+use agents::memory::Trace;
+use domains::{Observation, Transition};
+use fa::{QFunction, LinearQFunction};
 use geometry::{Space, ActionSpace};
-use policies::{Policy, Greedy};
+use policies::{Policy, FinitePolicy, Greedy};
+use serde::{Serialize, Deserialize};
 use std::collections::VecDeque;
 use std::marker::PhantomData;
-use utils::dot;
+use utils::{dot, logsumexp};
 
 
 /// Watkins' classical off policy temporal difference control algorithm.
 ///
 /// C. J. C. H. Watkins and P. Dayan, “Q-learning,” Mach. Learn., vol. 8, no. 3–4, pp. 279–292,
 /// 1992.
+#[derive(Serialize, Deserialize)]
 pub struct QLearning<S: Space, Q: QFunction<S>, P: Policy> {
     pub q_func: Q,
     pub policy: P,
@@ -47,12 +50,12 @@ impl<S: Space, Q, P> ControlAgent<S, ActionSpace> for QLearning<S, Q, P>
     where Q: QFunction<S>,
           P: Policy
 {
-    fn pi(&mut self, s: &S::Repr) -> usize {
-        self.policy.sample(self.q_func.evaluate(s).as_slice())
+    fn pi(&mut self, o: &Observation<S, ActionSpace>) -> usize {
+        self.policy.sample_legal(self.q_func.evaluate(o.state()).as_slice(), o.actions())
     }
 
-    fn evaluate_policy<T: Policy>(&self, p: &mut T, s: &S::Repr) -> usize {
-        p.sample(self.q_func.evaluate(s).as_slice())
+    fn evaluate_policy<T: Policy>(&self, p: &mut T, o: &Observation<S, ActionSpace>) -> usize {
+        p.sample_legal(self.q_func.evaluate(o.state()).as_slice(), o.actions())
     }
 
     fn handle_transition(&mut self, t: &Transition<S, ActionSpace>) {
@@ -62,16 +65,108 @@ impl<S: Space, Q, P> ControlAgent<S, ActionSpace> for QLearning<S, Q, P>
         let nqs = self.q_func.evaluate(ns);
 
         let a = t.action;
-        let na = Greedy.sample(nqs.as_slice());
+        let na = Greedy.sample_legal(nqs.as_slice(), t.to.actions());
 
-        let td_error = t.reward + self.gamma * nqs[na] - qs[a];
+        let td_error = t.reward + self.gamma.value() * nqs[na] - qs[a];
 
-        self.q_func.update_action(s, a, self.alpha * td_error);
+        self.q_func.update_action(s, a, self.alpha.value() * td_error);
+
+        tick!(Clock::Step => self.alpha, self.gamma);
+        tick!(Clock::Update => self.alpha, self.gamma);
+        self.policy.handle_update();
+        self.policy.handle_transition();
     }
 
     fn handle_terminal(&mut self, _: &S::Repr) {
-        self.alpha = self.alpha.step();
-        self.gamma = self.gamma.step();
+        tick!(Clock::Episode => self.alpha, self.gamma);
+
+        self.policy.handle_terminal();
+    }
+}
+
+
+/// Soft (entropy-regularised) Q-learning.
+///
+/// Replaces the hard maximum in the Q-learning bootstrap with the log-sum-exp
+/// of the next action-values at temperature `tau`, i.e. the soft value
+/// τ log Σ exp(Q(s', a') / τ).
+///
+/// Haarnoja, Tuomas, et al. "Reinforcement learning with deep energy-based
+/// policies." arXiv preprint arXiv:1702.08165 (2017).
+#[derive(Serialize, Deserialize)]
+pub struct SoftQLearning<S: Space, Q: QFunction<S>, P: Policy> {
+    pub q_func: Q,
+    pub policy: P,
+
+    pub alpha: Parameter,
+    pub gamma: Parameter,
+    pub tau: Parameter,
+
+    phantom: PhantomData<S>,
+}
+
+impl<S: Space, Q, P> SoftQLearning<S, Q, P>
+    where Q: QFunction<S>,
+          P: Policy
+{
+    pub fn new<T1, T2, T3>(q_func: Q, policy: P, alpha: T1, gamma: T2, tau: T3) -> Self
+        where T1: Into<Parameter>,
+              T2: Into<Parameter>,
+              T3: Into<Parameter>
+    {
+        SoftQLearning {
+            q_func: q_func,
+            policy: policy,
+
+            alpha: alpha.into(),
+            gamma: gamma.into(),
+            tau: tau.into(),
+
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<S: Space, Q, P> ControlAgent<S, ActionSpace> for SoftQLearning<S, Q, P>
+    where Q: QFunction<S>,
+          P: Policy
+{
+    fn pi(&mut self, o: &Observation<S, ActionSpace>) -> usize {
+        self.policy.sample_legal(self.q_func.evaluate(o.state()).as_slice(), o.actions())
+    }
+
+    fn evaluate_policy<T: Policy>(&self, p: &mut T, o: &Observation<S, ActionSpace>) -> usize {
+        p.sample_legal(self.q_func.evaluate(o.state()).as_slice(), o.actions())
+    }
+
+    fn handle_transition(&mut self, t: &Transition<S, ActionSpace>) {
+        let (s, ns) = (t.from.state(), t.to.state());
+
+        let qs = self.q_func.evaluate(s);
+        let nqs = self.q_func.evaluate(ns);
+
+        let a = t.action;
+
+        let legal = t.to.actions();
+
+        let tau = self.tau.value();
+        let soft_nv = tau * logsumexp(&nqs.iter()
+            .enumerate()
+            .filter(|&(na, _)| legal.is_empty() || legal.contains(&na))
+            .map(|(_, q)| q / tau)
+            .collect::<Vec<f64>>());
+        let td_error = t.reward + self.gamma.value() * soft_nv - qs[a];
+
+        self.q_func.update_action(s, a, self.alpha.value() * td_error);
+
+        tick!(Clock::Step => self.alpha, self.gamma, self.tau);
+        tick!(Clock::Update => self.alpha, self.gamma, self.tau);
+        self.policy.handle_update();
+        self.policy.handle_transition();
+    }
+
+    fn handle_terminal(&mut self, _: &S::Repr) {
+        tick!(Clock::Episode => self.alpha, self.gamma, self.tau);
 
         self.policy.handle_terminal();
     }
@@ -79,6 +174,10 @@ impl<S: Space, Q, P> ControlAgent<S, ActionSpace> for QLearning<S, Q, P>
 
 
 /// Classical on policy temporal difference control algorithm.
+///
+/// For meta-learned step sizes over linear features, use `SARSALambda` with a
+/// `Trace::Null`, which performs the same update.
+#[derive(Serialize, Deserialize)]
 pub struct SARSA<S: Space, Q: QFunction<S>, P: Policy> {
     pub q_func: Q,
     pub policy: P,
@@ -113,12 +212,12 @@ impl<S: Space, Q, P> ControlAgent<S, ActionSpace> for SARSA<S, Q, P>
     where Q: QFunction<S>,
           P: Policy
 {
-    fn pi(&mut self, s: &S::Repr) -> usize {
-        self.policy.sample(self.q_func.evaluate(s).as_slice())
+    fn pi(&mut self, o: &Observation<S, ActionSpace>) -> usize {
+        self.policy.sample_legal(self.q_func.evaluate(o.state()).as_slice(), o.actions())
     }
 
-    fn evaluate_policy<T: Policy>(&self, p: &mut T, s: &S::Repr) -> usize {
-        p.sample(self.q_func.evaluate(s).as_slice())
+    fn evaluate_policy<T: Policy>(&self, p: &mut T, o: &Observation<S, ActionSpace>) -> usize {
+        p.sample_legal(self.q_func.evaluate(o.state()).as_slice(), o.actions())
     }
 
     fn handle_transition(&mut self, t: &Transition<S, ActionSpace>) {
@@ -128,24 +227,118 @@ impl<S: Space, Q, P> ControlAgent<S, ActionSpace> for SARSA<S, Q, P>
         let nqs = self.q_func.evaluate(ns);
 
         let a = t.action;
-        let na = self.policy.sample(nqs.as_slice());
+        let na = self.policy.sample_legal(nqs.as_slice(), t.to.actions());
+
+        let td_error = t.reward + self.gamma.value() * nqs[na] - qs[a];
+
+        self.q_func.update_action(s, a, self.alpha.value() * td_error);
+
+        tick!(Clock::Step => self.alpha, self.gamma);
+        tick!(Clock::Update => self.alpha, self.gamma);
+        self.policy.handle_update();
+        self.policy.handle_transition();
+    }
+
+    fn handle_terminal(&mut self, _: &S::Repr) {
+        tick!(Clock::Episode => self.alpha, self.gamma);
+
+        self.policy.handle_terminal();
+    }
+}
+
+
+/// On policy temporal difference control with eligibility traces over
+/// state-action features.
+///
+/// Sutton, Richard S., and Andrew G. Barto. Reinforcement learning: An
+/// introduction. MIT press, 1998.
+#[derive(Serialize, Deserialize)]
+pub struct SARSALambda<S: Space, Q: LinearQFunction<S>, P: Policy> {
+    trace: Trace,
+
+    pub q_func: Q,
+    pub policy: P,
+
+    pub alpha: StepSize,
+    pub gamma: Parameter,
+
+    phantom: PhantomData<S>,
+}
+
+impl<S: Space, Q, P> SARSALambda<S, Q, P>
+    where Q: LinearQFunction<S>,
+          P: Policy
+{
+    pub fn new<T1, T2>(trace: Trace, q_func: Q, policy: P, alpha: T1, gamma: T2) -> Self
+        where T1: Into<StepSize>,
+              T2: Into<Parameter>
+    {
+        SARSALambda {
+            trace: trace,
 
-        let td_error = t.reward + self.gamma * nqs[na] - qs[a];
+            q_func: q_func,
+            policy: policy,
 
-        self.q_func.update_action(s, a, self.alpha * td_error);
+            alpha: alpha.into(),
+            gamma: gamma.into(),
+
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<S: Space, Q, P> ControlAgent<S, ActionSpace> for SARSALambda<S, Q, P>
+    where Q: LinearQFunction<S>,
+          P: Policy
+{
+    fn pi(&mut self, o: &Observation<S, ActionSpace>) -> usize {
+        self.policy.sample_legal(self.q_func.evaluate(o.state()).as_slice(), o.actions())
+    }
+
+    fn evaluate_policy<T: Policy>(&self, p: &mut T, o: &Observation<S, ActionSpace>) -> usize {
+        p.sample_legal(self.q_func.evaluate(o.state()).as_slice(), o.actions())
+    }
+
+    fn handle_transition(&mut self, t: &Transition<S, ActionSpace>) {
+        let (s, ns) = (t.from.state(), t.to.state());
+
+        let na = self.policy.sample_legal(self.q_func.evaluate(ns).as_slice(), t.to.actions());
+
+        let phi_s = self.q_func.phi_action(s, t.action);
+        let phi_ns = self.q_func.phi_action(ns, na);
+
+        self.trace.decay(self.gamma.value());
+        self.trace.update(&phi_s);
+
+        let td_error = t.reward + self.gamma.value() * self.q_func.evaluate_features(&phi_ns) -
+                       self.q_func.evaluate_features(&phi_s);
+
+        let dphi = self.gamma.value() * &phi_ns - &phi_s;
+        let increment = self.alpha.increment(&phi_s, self.trace.get(), &dphi, td_error);
+
+        self.q_func.update_features(&increment, 1.0);
+
+        self.alpha.tick(Clock::Step);
+        self.alpha.tick(Clock::Update);
+        tick!(Clock::Step => self.gamma);
+        tick!(Clock::Update => self.gamma);
+        self.policy.handle_update();
+        self.policy.handle_transition();
     }
 
     fn handle_terminal(&mut self, _: &S::Repr) {
-        self.alpha = self.alpha.step();
-        self.gamma = self.gamma.step();
+        self.alpha.tick(Clock::Episode);
+        tick!(Clock::Episode => self.gamma);
 
+        self.trace.decay(0.0);
         self.policy.handle_terminal();
     }
 }
 
 
 /// Expected SARSA.
-pub struct ExpectedSARSA<S: Space, Q: QFunction<S>, P: Policy> {
+#[derive(Serialize, Deserialize)]
+pub struct ExpectedSARSA<S: Space, Q: QFunction<S>, P: FinitePolicy> {
     pub q_func: Q,
     pub policy: P,
 
@@ -157,7 +350,7 @@ pub struct ExpectedSARSA<S: Space, Q: QFunction<S>, P: Policy> {
 
 impl<S: Space, Q, P> ExpectedSARSA<S, Q, P>
     where Q: QFunction<S>,
-          P: Policy
+          P: FinitePolicy
 {
     pub fn new<T1, T2>(q_func: Q, policy: P, alpha: T1, gamma: T2) -> Self
         where T1: Into<Parameter>,
@@ -177,14 +370,14 @@ impl<S: Space, Q, P> ExpectedSARSA<S, Q, P>
 
 impl<S: Space, Q, P> ControlAgent<S, ActionSpace> for ExpectedSARSA<S, Q, P>
     where Q: QFunction<S>,
-          P: Policy
+          P: FinitePolicy
 {
-    fn pi(&mut self, s: &S::Repr) -> usize {
-        self.policy.sample(self.q_func.evaluate(s).as_slice())
+    fn pi(&mut self, o: &Observation<S, ActionSpace>) -> usize {
+        self.policy.sample_legal(self.q_func.evaluate(o.state()).as_slice(), o.actions())
     }
 
-    fn evaluate_policy<T: Policy>(&self, p: &mut T, s: &S::Repr) -> usize {
-        p.sample(self.q_func.evaluate(s).as_slice())
+    fn evaluate_policy<T: Policy>(&self, p: &mut T, o: &Observation<S, ActionSpace>) -> usize {
+        p.sample_legal(self.q_func.evaluate(o.state()).as_slice(), o.actions())
     }
 
     fn handle_transition(&mut self, t: &Transition<S, ActionSpace>) {
@@ -195,15 +388,19 @@ impl<S: Space, Q, P> ControlAgent<S, ActionSpace> for ExpectedSARSA<S, Q, P>
 
         let a = t.action;
 
-        let exp_nqs = dot(&nqs, &self.policy.probabilities(nqs.as_slice()));
-        let td_error = t.reward + self.gamma * exp_nqs - qs[a];
+        let exp_nqs = dot(&nqs, &self.policy.probabilities_legal(nqs.as_slice(), t.to.actions()));
+        let td_error = t.reward + self.gamma.value() * exp_nqs - qs[a];
+
+        self.q_func.update_action(s, a, self.alpha.value() * td_error);
 
-        self.q_func.update_action(s, a, self.alpha * td_error);
+        tick!(Clock::Step => self.alpha, self.gamma);
+        tick!(Clock::Update => self.alpha, self.gamma);
+        self.policy.handle_update();
+        self.policy.handle_transition();
     }
 
     fn handle_terminal(&mut self, _: &S::Repr) {
-        self.alpha = self.alpha.step();
-        self.gamma = self.gamma.step();
+        tick!(Clock::Episode => self.alpha, self.gamma);
 
         self.policy.handle_terminal();
     }
@@ -218,9 +415,15 @@ impl<S: Space, Q, P> ControlAgent<S, ActionSpace> for ExpectedSARSA<S, Q, P>
 /// * `0` - `ExpectedSARSA` | `TreeBackup`
 /// * `1` - `SARSA`
 ///
+/// Unlike the other parameters, `sigma` advances on `Clock::Step` unless it is
+/// given an explicit clock.
+///
 /// De Asis, Kristopher, et al. "Multi-step Reinforcement Learning: A Unifying
 /// Algorithm." arXiv preprint arXiv:1703.01327 (2017).
-pub struct QSigma<S: Space, Q: QFunction<S>, P: Policy> {
+#[derive(Serialize, Deserialize)]
+#[serde(bound(serialize = "Q: Serialize, P: Serialize, S::Repr: Serialize",
+              deserialize = "Q: Deserialize<'de>, P: Deserialize<'de>, S::Repr: Deserialize<'de>"))]
+pub struct QSigma<S: Space, Q: QFunction<S>, P: FinitePolicy> {
     pub q_func: Q,
     pub policy: P,
 
@@ -235,7 +438,7 @@ pub struct QSigma<S: Space, Q: QFunction<S>, P: Policy> {
 
 impl<S: Space, Q, P> QSigma<S, Q, P>
     where Q: QFunction<S>,
-          P: Policy
+          P: FinitePolicy
 {
     pub fn new<T1, T2, T3>(q_func: Q,
                            policy: P,
@@ -248,13 +451,20 @@ impl<S: Space, Q, P> QSigma<S, Q, P>
               T2: Into<Parameter>,
               T3: Into<Parameter>
     {
+        // Sigma is resampled per step unless the caller says otherwise:
+        let sigma: Parameter = sigma.into();
+        let sigma = match sigma {
+            p @ Parameter::Clocked(..) => p,
+            p => p.with_clock(Clock::Step),
+        };
+
         QSigma {
             q_func: q_func,
             policy: policy,
 
             alpha: alpha.into(),
             gamma: gamma.into(),
-            sigma: sigma.into(),
+            sigma: sigma,
 
             n_steps: n_steps,
 
@@ -283,12 +493,17 @@ impl<S: Space, Q, P> QSigma<S, Q, P>
 
         self.q_func.update_action(&self.backup[0].s1,
                                   self.backup[0].a1,
-                                  self.alpha * isr * td_error);
+                                  self.alpha.value() * isr * td_error);
 
         self.backup.pop_front();
+
+        tick!(Clock::Update => self.alpha, self.gamma, self.sigma);
+        self.policy.handle_update();
     }
 }
 
+#[derive(Serialize, Deserialize)]
+#[serde(bound(serialize = "S::Repr: Serialize", deserialize = "S::Repr: Deserialize<'de>"))]
 struct BackupEntry<S: Space> {
     pub s1: S::Repr,
     pub a1: usize,
@@ -306,14 +521,14 @@ struct BackupEntry<S: Space> {
 
 impl<S: Space, Q, P> ControlAgent<S, ActionSpace> for QSigma<S, Q, P>
     where Q: QFunction<S>,
-          P: Policy
+          P: FinitePolicy
 {
-    fn pi(&mut self, s: &S::Repr) -> usize {
-        self.policy.sample(self.q_func.evaluate(s).as_slice())
+    fn pi(&mut self, o: &Observation<S, ActionSpace>) -> usize {
+        self.policy.sample_legal(self.q_func.evaluate(o.state()).as_slice(), o.actions())
     }
 
-    fn evaluate_policy<T: Policy>(&self, p: &mut T, s: &S::Repr) -> usize {
-        p.sample(self.q_func.evaluate(s).as_slice())
+    fn evaluate_policy<T: Policy>(&self, p: &mut T, o: &Observation<S, ActionSpace>) -> usize {
+        p.sample_legal(self.q_func.evaluate(o.state()).as_slice(), o.actions())
     }
 
     fn handle_transition(&mut self, t: &Transition<S, ActionSpace>) {
@@ -322,17 +537,14 @@ impl<S: Space, Q, P> ControlAgent<S, ActionSpace> for QSigma<S, Q, P>
         let q = self.q_func.evaluate_action(s, t.action);
         let nqs = self.q_func.evaluate(ns);
 
-        let npi = self.policy.probabilities(nqs.as_slice());
+        let npi = self.policy.probabilities_legal(nqs.as_slice(), t.to.actions());
         let exp_nqs = dot(&nqs, &npi);
 
-        let na = self.policy.sample(nqs.as_slice());
+        let na = self.policy.sample_legal(nqs.as_slice(), t.to.actions());
         let nq = nqs[na];
 
-        let sigma = {
-            self.sigma = self.sigma.step();
-            self.sigma.value()
-        };
-        let td_error = t.reward + self.gamma * (sigma * nq + (1.0 - sigma) * exp_nqs) - q;
+        let sigma = self.sigma.value();
+        let td_error = t.reward + self.gamma.value() * (sigma * nq + (1.0 - sigma) * exp_nqs) - q;
 
         // Update backup sequence:
         self.backup.push_back(BackupEntry {
@@ -347,20 +559,22 @@ impl<S: Space, Q, P> ControlAgent<S, ActionSpace> for QSigma<S, Q, P>
 
             sigma: sigma,
             pi: npi[na],
-            mu: Greedy.probabilities(&nqs)[na],
+            mu: Greedy.probabilities_legal(&nqs, t.to.actions())[na],
         });
 
         // Learn of latest backup sequence if we have `n_steps` entries:
         if self.backup.len() >= self.n_steps {
             self.consume_backup()
         }
+
+        tick!(Clock::Step => self.alpha, self.gamma, self.sigma);
+        self.policy.handle_transition();
     }
 
     fn handle_terminal(&mut self, _: &S::Repr) {
         // TODO: Handle terminal update according to Sutton's pseudocode.
         //       It's likely that this will require a change to the interface
-        self.alpha = self.alpha.step();
-        self.gamma = self.gamma.step();
+        tick!(Clock::Episode => self.alpha, self.gamma, self.sigma);
 
         self.policy.handle_terminal();
     }
@@ -368,3 +582,131 @@ impl<S: Space, Q, P> ControlAgent<S, ActionSpace> for QSigma<S, Q, P>
 
 // TODO:
 // PQ(lambda) - http://proceedings.mlr.press/v32/sutton14.pdf
+
+
+#[cfg(test)]
+mod tests {
+    use {Parameter, Clock};
+    use super::{QLearning, SARSA, SARSALambda, ExpectedSARSA, QSigma};
+    use agents::{ControlAgent, StepSize};
+    use agents::memory::Trace;
+    use domains::{Observation, Transition};
+    use fa::{Function, Linear};
+    use fa::projection::UniformGrid;
+    use geometry::{RegularSpace, ActionSpace};
+    use geometry::dimensions::{Continuous, Partitioned};
+    use ndarray::Array1;
+    use policies::{FinitePolicy, Greedy, EpsilonGreedy};
+
+    fn q_func(n_actions: usize) -> Linear<RegularSpace<Continuous>, UniformGrid> {
+        Linear::new(UniformGrid::new(RegularSpace::new().push(Partitioned::new(0.0, 1.0, 2))),
+                    n_actions)
+    }
+
+    type Obs = Observation<RegularSpace<Continuous>, ActionSpace>;
+
+    fn full(x: f64, actions: Vec<usize>) -> Obs {
+        Observation::Full {
+            state: vec![x],
+            actions: actions,
+        }
+    }
+
+    fn transition(from: f64, action: usize, reward: f64, to: Obs)
+                  -> Transition<RegularSpace<Continuous>, ActionSpace> {
+        Transition {
+            from: full(from, vec![]),
+            action: action,
+            reward: reward,
+            to: to,
+        }
+    }
+
+    #[test]
+    fn test_sarsa_lambda_null_trace() {
+        let mut sarsa = SARSA::new(q_func(2), Greedy, 0.1, 0.9);
+        let mut sarsa_lambda = SARSALambda::new(Trace::Null { eligibility: Array1::zeros((4,)) },
+                                                q_func(2), Greedy, StepSize::from(0.1), 0.9);
+
+        for i in 0..50 {
+            let t = transition(0.25, i % 2, 1.0, full(0.75, vec![]));
+
+            sarsa.handle_transition(&t);
+            sarsa_lambda.handle_transition(&t);
+        }
+
+        for x in &[0.25, 0.75] {
+            let qs: Vec<f64> = sarsa.q_func.evaluate(&vec![*x]);
+            let qs_lambda: Vec<f64> = sarsa_lambda.q_func.evaluate(&vec![*x]);
+
+            for (q, ql) in qs.iter().zip(qs_lambda.iter()) {
+                assert!((q - ql).abs() < 1e-10);
+            }
+        }
+    }
+
+    #[test]
+    fn test_clocks() {
+        let alpha = Parameter::linear(1.0, 0.0, 10).with_clock(Clock::Step);
+        let epsilon = Parameter::linear(1.0, 0.0, 10).with_clock(Clock::Update);
+        let mut agent = QLearning::new(q_func(2), EpsilonGreedy::new(epsilon), alpha, 0.9);
+
+        for _ in 0..4 {
+            agent.handle_transition(&transition(0.25, 0, 1.0, full(0.75, vec![])));
+        }
+        agent.handle_terminal(&vec![0.75]);
+
+        // Both advance once per transition, but not at the end of the episode:
+        assert!((agent.alpha.value() - 0.6).abs() < 1e-10);
+
+        let ps = agent.policy.probabilities(&[1.0, 0.0]);
+        assert!((ps[0] - 0.7).abs() < 1e-10 && (ps[1] - 0.3).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_q_sigma_step_clock() {
+        let mut agent = QSigma::new(q_func(2), Greedy, 0.1, 0.9,
+                                    Parameter::linear(1.0, 0.0, 10), 2);
+
+        agent.handle_transition(&transition(0.25, 0, 1.0, full(0.75, vec![])));
+        agent.handle_transition(&transition(0.75, 1, 1.0, full(0.25, vec![])));
+
+        assert!((agent.sigma.value() - 0.8).abs() < 1e-10);
+
+        let mut agent = QSigma::new(q_func(2), Greedy, 0.1, 0.9,
+                                    Parameter::linear(1.0, 0.0, 10).with_clock(Clock::Episode), 2);
+
+        agent.handle_transition(&transition(0.25, 0, 1.0, full(0.75, vec![])));
+        assert_eq!(agent.sigma.value(), 1.0);
+
+        agent.handle_terminal(&vec![0.75]);
+        assert!((agent.sigma.value() - 0.9).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_q_learning_legal_target() {
+        let mut agent = QLearning::new(q_func(2), Greedy, 0.5, 0.9);
+
+        // Action 1 has the largest value in the next state, but is illegal:
+        agent.q_func.weights[(1, 1)] = 10.0;
+        agent.handle_transition(&transition(0.25, 0, 1.0, full(0.75, vec![0])));
+
+        let qs: Vec<f64> = agent.q_func.evaluate(&vec![0.25]);
+        assert!((qs[0] - 0.5).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_expected_sarsa_legal_expectation() {
+        let mut agent = ExpectedSARSA::new(q_func(3), EpsilonGreedy::new(0.5), 0.5, 0.9);
+
+        agent.q_func.weights[(1, 1)] = 10.0;
+        agent.q_func.weights[(1, 2)] = 4.0;
+
+        // Over the legal actions [0, 2] the policy is [0.25, 0.75], so the
+        // expectation is 3.0 and the target 1.0 + 0.9 * 3.0:
+        agent.handle_transition(&transition(0.25, 0, 1.0, full(0.75, vec![0, 2])));
+
+        let qs: Vec<f64> = agent.q_func.evaluate(&vec![0.25]);
+        assert!((qs[0] - 1.85).abs() < 1e-10);
+    }
+}
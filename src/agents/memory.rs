@@ -1,7 +1,10 @@
 use Parameter;
 use ndarray::Array1;
+use rand::{Rng, thread_rng, ThreadRng};
+use std::collections::VecDeque;
 
 
+#[derive(Serialize, Deserialize)]
 pub enum Trace {
     Accumulating {
         lambda: Parameter,
@@ -28,9 +31,9 @@ impl Trace {
 
     pub fn decay(&mut self, rate: f64) {
         match self {
-            &mut Trace::Accumulating { ref mut eligibility, lambda } |
-            &mut Trace::Replacing { ref mut eligibility, lambda } => {
-                *eligibility *= rate * lambda;
+            &mut Trace::Accumulating { ref mut eligibility, ref lambda } |
+            &mut Trace::Replacing { ref mut eligibility, ref lambda } => {
+                *eligibility *= rate * lambda.value();
             },
             &mut Trace::Null { ref mut eligibility } => *eligibility *= rate,
         }
@@ -50,9 +53,62 @@ impl Trace {
 }
 
 
+/// Fixed capacity FIFO store of past experience with uniform sampling.
+#[derive(Serialize, Deserialize)]
+pub struct ReplayBuffer<T> {
+    capacity: usize,
+    buffer: VecDeque<T>,
+
+    #[serde(skip, default = "thread_rng")]
+    rng: ThreadRng,
+}
+
+impl<T> ReplayBuffer<T> {
+    pub fn new(capacity: usize) -> Self {
+        ReplayBuffer {
+            capacity: capacity,
+            buffer: VecDeque::with_capacity(capacity),
+
+            rng: thread_rng(),
+        }
+    }
+
+    /// Adds an entry to the buffer, evicting the oldest if at capacity.
+    pub fn push(&mut self, entry: T) {
+        if self.buffer.len() >= self.capacity {
+            self.buffer.pop_front();
+        }
+
+        self.buffer.push_back(entry);
+    }
+
+    /// Draws `n` entries uniformly at random, with replacement.
+    pub fn sample(&mut self, n: usize) -> Vec<&T> {
+        let len = self.buffer.len();
+        let indices: Vec<usize> = (0..n).map(|_| self.rng.gen_range(0, len)).collect();
+
+        let buffer = &self.buffer;
+
+        indices.into_iter().map(|i| &buffer[i]).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.buffer.clear();
+    }
+}
+
+
 #[cfg(test)]
 mod tests {
-    use super::Trace;
+    use super::{Trace, ReplayBuffer};
     use ndarray::{ArrayBase, arr1};
 
     #[test]
@@ -98,4 +154,21 @@ mod tests {
         trace.update(&arr1(&[1.0f64; 10]));
         assert_eq!(trace.get(), &arr1(&[1.0f64; 10]));
     }
+
+    #[test]
+    fn test_replay_buffer() {
+        let mut rb = ReplayBuffer::new(3);
+        assert!(rb.is_empty());
+
+        for i in 0..5 {
+            rb.push(i);
+        }
+
+        assert_eq!(rb.len(), 3);
+
+        let batch = rb.sample(100);
+
+        assert_eq!(batch.len(), 100);
+        assert!(batch.iter().all(|&&x| x >= 2 && x < 5));
+    }
 }
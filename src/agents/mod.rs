@@ -1,10 +1,12 @@
 pub mod memory;
+pub mod step_size;
 
 pub mod control;
 pub mod prediction;
 
 pub use self::control::ControlAgent;
 pub use self::prediction::PredictionAgent;
+pub use self::step_size::StepSize;
 
 
 // TODO
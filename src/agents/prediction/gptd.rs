@@ -0,0 +1,218 @@
+use {Parameter, Clock};
+use agents::PredictionAgent;
+use fa::Function;
+use geometry::RegularSpace;
+use geometry::dimensions::Continuous;
+use geometry::kernels::Kernel;
+use ndarray::{Array1, Array2};
+
+
+/// Sparse online Gaussian process temporal difference learning.
+///
+/// Maintains a dictionary of approximately linearly independent states (in the
+/// feature space of the kernel) and the posterior mean and covariance of the
+/// value function over them. The sparsification threshold `nu` controls the
+/// size of the dictionary, while `sigma` is the standard deviation of the
+/// observation noise.
+///
+/// Engel, Yaakov, Shie Mannor, and Ron Meir. "Bayes meets Bellman: The
+/// Gaussian process approach to temporal difference learning." Proceedings of
+/// the 20th International Conference on Machine Learning (ICML-03). 2003.
+#[derive(Serialize, Deserialize)]
+pub struct GPTD<K: Kernel> {
+    pub kernel: K,
+
+    dictionary: Vec<Vec<f64>>,
+    k_inv: Array2<f64>,
+    weights: Array1<f64>,
+    covariance: Array2<f64>,
+
+    pub nu: f64,
+    pub sigma: f64,
+    pub gamma: Parameter,
+}
+
+impl<K: Kernel> GPTD<K> {
+    pub fn new<T: Into<Parameter>>(kernel: K, nu: f64, sigma: f64, gamma: T) -> Self {
+        GPTD {
+            kernel: kernel,
+
+            dictionary: vec![],
+            k_inv: Array2::zeros((0, 0)),
+            weights: Array1::zeros((0,)),
+            covariance: Array2::zeros((0, 0)),
+
+            nu: nu,
+            sigma: sigma,
+            gamma: gamma.into(),
+        }
+    }
+
+    /// Returns the states currently held in the sparsification dictionary.
+    pub fn dictionary(&self) -> &Vec<Vec<f64>> {
+        &self.dictionary
+    }
+
+    /// Returns the posterior variance of the value estimate at a given state.
+    pub fn variance(&self, s: &Vec<f64>) -> f64 {
+        let k = self.kernel_vector(s);
+
+        self.kernel.kernel(s, s) - k.dot(&self.covariance.dot(&k))
+    }
+
+    fn kernel_vector(&self, s: &[f64]) -> Array1<f64> {
+        Array1::from_shape_fn((self.dictionary.len(),),
+                              |i| self.kernel.kernel(&self.dictionary[i], s))
+    }
+
+    fn initialise(&mut self, s: &Vec<f64>) {
+        self.dictionary = vec![s.clone()];
+        self.k_inv = Array2::from_elem((1, 1), 1.0 / self.kernel.kernel(s, s));
+        self.weights = Array1::zeros((1,));
+        self.covariance = Array2::zeros((1, 1));
+    }
+
+    fn expand(&mut self, s: &Vec<f64>, a: &Array1<f64>, delta: f64) {
+        let m = self.dictionary.len();
+
+        let k_inv = Array2::from_shape_fn((m + 1, m + 1), |(i, j)| {
+            if i < m && j < m {
+                self.k_inv[(i, j)] + a[i] * a[j] / delta
+            } else if i < m {
+                -a[i] / delta
+            } else if j < m {
+                -a[j] / delta
+            } else {
+                1.0 / delta
+            }
+        });
+        let weights = Array1::from_shape_fn((m + 1,), |i| {
+            if i < m { self.weights[i] } else { 0.0 }
+        });
+        let covariance = Array2::from_shape_fn((m + 1, m + 1), |(i, j)| {
+            if i < m && j < m { self.covariance[(i, j)] } else { 0.0 }
+        });
+
+        self.k_inv = k_inv;
+        self.weights = weights;
+        self.covariance = covariance;
+
+        self.dictionary.push(s.clone());
+    }
+}
+
+impl<K: Kernel> Function<Vec<f64>, f64> for GPTD<K> {
+    fn evaluate(&self, s: &Vec<f64>) -> f64 {
+        self.kernel_vector(s).dot(&self.weights)
+    }
+}
+
+impl<K: Kernel> PredictionAgent<RegularSpace<Continuous>> for GPTD<K> {
+    fn handle_transition(&mut self, s: &Vec<f64>, ns: &Vec<f64>, r: f64) -> Option<f64> {
+        if self.dictionary.is_empty() {
+            self.initialise(s);
+        }
+
+        let gamma = self.gamma.value();
+
+        let k_s = self.kernel_vector(s);
+        let k_ns = self.kernel_vector(ns);
+        let k_nsns = self.kernel.kernel(ns, ns);
+
+        let a_s = self.k_inv.dot(&k_s);
+        let a_ns = self.k_inv.dot(&k_ns);
+
+        // Approximate linear dependence test:
+        let delta = k_nsns - k_ns.dot(&a_ns);
+
+        let dk = &k_s - &(gamma * &k_ns);
+        let td_error = r - dk.dot(&self.weights);
+
+        let c_dk = self.covariance.dot(&dk);
+        let (c, s_t) = if delta > self.nu {
+            let m = self.dictionary.len();
+
+            let dk_tt = a_s.dot(&(&k_s - &(2.0 * gamma * &k_ns))) + gamma * gamma * k_nsns;
+            let c = Array1::from_shape_fn((m + 1,), |i| {
+                if i < m { a_s[i] - c_dk[i] } else { -gamma }
+            });
+            let s_t = self.sigma * self.sigma + dk_tt - dk.dot(&c_dk);
+
+            self.expand(ns, &a_ns, delta);
+
+            (c, s_t)
+        } else {
+            let c = &a_s - &(gamma * &a_ns) - &c_dk;
+            let s_t = self.sigma * self.sigma + c.dot(&dk);
+
+            (c, s_t)
+        };
+
+        self.weights.scaled_add(td_error / s_t, &c);
+
+        let n = c.len();
+        let c_view = c.view().into_shape((n, 1)).unwrap();
+        self.covariance.scaled_add(1.0 / s_t, &c_view.dot(&c_view.t()));
+
+        tick!(Clock::Step => self.gamma);
+        tick!(Clock::Update => self.gamma);
+
+        Some(td_error)
+    }
+
+    fn handle_terminal(&mut self, _: &Vec<f64>) {
+        tick!(Clock::Episode => self.gamma);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::GPTD;
+    use agents::PredictionAgent;
+    use fa::Function;
+    use geometry::kernels::Gaussian;
+
+    #[test]
+    fn test_self_loop() {
+        let mut agent = GPTD::new(Gaussian::new(1.0, 1.0), 0.1, 1e-3, 0.5);
+        let s = vec![0.0];
+
+        for _ in 0..100 {
+            agent.handle_transition(&s, &s, 1.0);
+        }
+
+        assert_eq!(agent.dictionary().len(), 1);
+        assert!((agent.evaluate(&s) - 2.0).abs() < 1e-2);
+    }
+
+    #[test]
+    fn test_sparsification() {
+        let mut agent = GPTD::new(Gaussian::new(1.0, 1.0), 0.1, 1e-1, 0.9);
+
+        for i in 0..100 {
+            let s = vec![i as f64 / 100.0];
+            let ns = vec![(i + 1) as f64 / 100.0];
+
+            agent.handle_transition(&s, &ns, 0.0);
+        }
+
+        assert!(agent.dictionary().len() < 10);
+    }
+
+    #[test]
+    fn test_variance() {
+        let mut agent = GPTD::new(Gaussian::new(1.0, 1.0), 0.1, 1e-1, 0.9);
+        let s = vec![0.0];
+        let far = vec![100.0];
+
+        agent.handle_transition(&s, &s, 1.0);
+        let v1 = agent.variance(&s);
+
+        agent.handle_transition(&s, &s, 1.0);
+        let v2 = agent.variance(&s);
+
+        assert!(v2 < v1);
+        assert!((agent.variance(&far) - 1.0).abs() < 1e-6);
+    }
+}
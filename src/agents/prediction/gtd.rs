@@ -1,15 +1,15 @@
-use Parameter;
-use agents::PredictionAgent;
-use agents::memory::Trace;
+use {Parameter, Clock};
+use agents::{PredictionAgent, StepSize};
 use fa::{VFunction, Projection, Linear};
 use geometry::Space;
 
 
+#[derive(Serialize, Deserialize)]
 pub struct GTD2<S: Space, P: Projection<S>> {
     pub v_func: Linear<S, P>,
     pub a_func: Linear<S, P>,
 
-    pub alpha: Parameter,
+    pub alpha: StepSize,
     pub beta: Parameter,
     pub gamma: Parameter,
 }
@@ -21,7 +21,7 @@ impl<S: Space, P: Projection<S>> GTD2<S, P> {
                            beta: T2,
                            gamma: T3)
                            -> Self
-        where T1: Into<Parameter>,
+        where T1: Into<StepSize>,
               T2: Into<Parameter>,
               T3: Into<Parameter>
     {
@@ -47,30 +47,37 @@ impl<S: Space, V> PredictionAgent<S> for GTD2<S, V>
         let phi_s = self.v_func.project(s);
         let phi_ns = self.v_func.project(ns);
 
-        let td_error = r + self.gamma * self.v_func.evaluate_phi(&phi_ns) -
+        let td_error = r + self.gamma.value() * self.v_func.evaluate_phi(&phi_ns) -
                        self.v_func.evaluate_phi(&phi_s);
         let td_estimate = self.a_func.evaluate_phi(&phi_s);
 
-        self.v_func.update_phi(&(&phi_s - &(self.gamma.value() * &phi_ns)),
-                               self.alpha * td_estimate);
-        self.a_func.update_phi(&phi_s, self.beta * (td_error - td_estimate));
+        let dphi = self.gamma.value() * &phi_ns - &phi_s;
+        let increment = self.alpha.increment(&phi_s, &(-&dphi), &dphi, td_estimate);
+
+        self.v_func.update_phi(&increment, 1.0);
+        self.a_func.update_phi(&phi_s, self.beta.value() * (td_error - td_estimate));
+
+        self.alpha.tick(Clock::Step);
+        self.alpha.tick(Clock::Update);
+        tick!(Clock::Step => self.beta, self.gamma);
+        tick!(Clock::Update => self.beta, self.gamma);
 
         Some(td_error)
     }
 
     fn handle_terminal(&mut self, _: &S::Repr) {
-        self.alpha = self.alpha.step();
-        self.beta = self.alpha.step();
-        self.gamma = self.gamma.step();
+        self.alpha.tick(Clock::Episode);
+        tick!(Clock::Episode => self.beta, self.gamma);
     }
 }
 
 
+#[derive(Serialize, Deserialize)]
 pub struct TDC<S: Space, P: Projection<S>> {
     pub v_func: Linear<S, P>,
     pub a_func: Linear<S, P>,
 
-    pub alpha: Parameter,
+    pub alpha: StepSize,
     pub beta: Parameter,
     pub gamma: Parameter,
 }
@@ -82,7 +89,7 @@ impl<S: Space, P: Projection<S>> TDC<S, P> {
                            beta: T2,
                            gamma: T3)
                            -> Self
-        where T1: Into<Parameter>,
+        where T1: Into<StepSize>,
               T2: Into<Parameter>,
               T3: Into<Parameter>
     {
@@ -108,22 +115,30 @@ impl<S: Space, V> PredictionAgent<S> for TDC<S, V>
         let phi_s = self.v_func.project(s);
         let phi_ns = self.v_func.project(ns);
 
-        let td_error = r + self.gamma * self.v_func.evaluate_phi(&phi_ns) -
+        let td_error = r + self.gamma.value() * self.v_func.evaluate_phi(&phi_ns) -
                        self.v_func.evaluate_phi(&phi_s);
         let td_estimate = self.a_func.evaluate_phi(&phi_s);
 
-        self.v_func
-            .update_phi(&(&(td_error * &phi_s) - &(self.gamma.value() * td_estimate * &phi_ns)),
-                        self.alpha.value());
-        self.a_func.update_phi(&phi_s, self.beta * (td_error - td_estimate));
+        // Step sizes are adapted on the TD error alone; the gradient correction
+        // is then applied with the same per-feature step sizes:
+        let dphi = self.gamma.value() * &phi_ns - &phi_s;
+        let increment = self.alpha.increment(&phi_s, &phi_s, &dphi, td_error);
+        let correction = self.alpha.scale(&(self.gamma.value() * td_estimate * &phi_ns));
+
+        self.v_func.update_phi(&(increment - correction), 1.0);
+        self.a_func.update_phi(&phi_s, self.beta.value() * (td_error - td_estimate));
+
+        self.alpha.tick(Clock::Step);
+        self.alpha.tick(Clock::Update);
+        tick!(Clock::Step => self.beta, self.gamma);
+        tick!(Clock::Update => self.beta, self.gamma);
 
         Some(td_error)
     }
 
     fn handle_terminal(&mut self, _: &S::Repr) {
-        self.alpha = self.alpha.step();
-        self.beta = self.alpha.step();
-        self.gamma = self.gamma.step();
+        self.alpha.tick(Clock::Episode);
+        tick!(Clock::Episode => self.beta, self.gamma);
     }
 }
 
@@ -134,3 +149,43 @@ impl<S: Space, V> PredictionAgent<S> for TDC<S, V>
 // True online GTD(lambda) - http://citeseerx.ist.psu.edu/viewdoc/download?doi=10.1.1.487.2451&rep=rep1&type=pdf
 // GTD2(lambda)-MP - https://arxiv.org/pdf/1602.08771.pdf
 // TDC(lambda)-MP - https://arxiv.org/pdf/1602.08771.pdf
+
+
+#[cfg(test)]
+mod tests {
+    use super::TDC;
+    use agents::{PredictionAgent, StepSize};
+    use fa::{Function, Linear};
+    use fa::projection::UniformGrid;
+    use geometry::RegularSpace;
+    use geometry::dimensions::{Continuous, Partitioned};
+    use ndarray::arr1;
+
+    type Grid = Linear<RegularSpace<Continuous>, UniformGrid>;
+
+    fn grid() -> Grid {
+        Linear::new(UniformGrid::new(RegularSpace::new().push(Partitioned::new(0.0, 1.0, 2))), 1)
+    }
+
+    #[test]
+    fn test_tdc_step_size_adaptation() {
+        let alpha = StepSize::idbd(0.01, 0.01);
+        let mut agent = TDC::new(Linear::new(grid(), 1), Linear::new(grid(), 1), alpha, 0.1, 0.0);
+
+        // With gamma = 0 the correction term vanishes, so the step size must
+        // follow IDBD on the TD error of the LMS rule:
+        let mut reference = StepSize::idbd(0.01, 0.01);
+        let (phi, dphi) = (arr1(&[1.0, 0.0]), arr1(&[-1.0, 0.0]));
+        let mut w = 0.0;
+
+        for _ in 0..200 {
+            agent.handle_transition(&vec![0.25], &vec![0.75], 1.0);
+            w += reference.increment(&phi, &phi, &dphi, 1.0 - w)[0];
+        }
+
+        let v: f64 = agent.v_func.evaluate(&vec![0.25]);
+
+        assert!((v - w).abs() < 1e-12);
+        assert!(agent.alpha.scale(&phi)[0] > 0.01);
+    }
+}
@@ -1,10 +1,14 @@
-use Parameter;
+use {Parameter, Clock};
 use agents::PredictionAgent;
 use fa::VFunction;
 use geometry::Space;
+use serde::{Serialize, Deserialize};
 use std::marker::PhantomData;
 
 
+#[derive(Serialize, Deserialize)]
+#[serde(bound(serialize = "V: Serialize, S::Repr: Serialize",
+              deserialize = "V: Deserialize<'de>, S::Repr: Deserialize<'de>"))]
 pub struct EveryVisitMC<S: Space, V: VFunction<S>> {
     pub v_func: V,
     observations: Vec<(S::Repr, f64)>,
@@ -37,10 +41,12 @@ impl<S: Space, V> EveryVisitMC<S, V>
         let mut sum = 0.0;
 
         for (s, r) in self.observations.drain(0..).rev() {
-            sum = r + self.gamma * sum;
+            sum = r + self.gamma.value() * sum;
 
             let v_est = self.v_func.evaluate(&s);
-            self.v_func.update(&s, self.alpha * (sum - v_est));
+            self.v_func.update(&s, self.alpha.value() * (sum - v_est));
+
+            tick!(Clock::Update => self.alpha, self.gamma);
         }
     }
 }
@@ -50,6 +56,7 @@ impl<S: Space, V> PredictionAgent<S> for EveryVisitMC<S, V>
 {
     fn handle_transition(&mut self, s: &S::Repr, _: &S::Repr, r: f64) -> Option<f64> {
         self.observations.push((s.clone(), r));
+        tick!(Clock::Step => self.alpha, self.gamma);
 
         None
     }
@@ -57,7 +64,6 @@ impl<S: Space, V> PredictionAgent<S> for EveryVisitMC<S, V>
     fn handle_terminal(&mut self, _: &S::Repr) {
         self.propagate();
 
-        self.alpha = self.alpha.step();
-        self.gamma = self.gamma.step();
+        tick!(Clock::Episode => self.alpha, self.gamma);
     }
 }
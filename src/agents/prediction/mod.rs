@@ -10,6 +10,8 @@ pub trait PredictionAgent<S: Space> {
 pub mod mc;
 pub mod td;
 pub mod gtd;
+pub mod sr;
+pub mod gptd;
 
 
 // TODO:
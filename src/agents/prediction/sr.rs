@@ -0,0 +1,177 @@
+use {Parameter, Clock};
+use agents::PredictionAgent;
+use fa::{Function, QFunction, Projection, Linear};
+use geometry::Space;
+use ndarray::Array1;
+
+
+/// Successor features prediction algorithm.
+///
+/// Learns the discounted expected feature occupancy ψ(s) ≡ E[Σ γ^t φ(s_t)] by
+/// TD and, separately, a linear regression of the reward onto the features.
+/// The value function is then given by V(s) = ψ(s)·w, such that a new set of
+/// reward weights can be evaluated without any further learning.
+///
+/// Barreto, André, et al. "Successor features for transfer in reinforcement
+/// learning." Advances in Neural Information Processing Systems. 2017.
+#[derive(Serialize, Deserialize)]
+pub struct SuccessorFeatures<S: Space, P: Projection<S>> {
+    pub psi_func: Linear<S, P>,
+    pub reward_weights: Array1<f64>,
+
+    pub alpha: Parameter,
+    pub beta: Parameter,
+    pub gamma: Parameter,
+}
+
+impl<S: Space, P: Projection<S>> SuccessorFeatures<S, P> {
+    pub fn new<T1, T2, T3>(projector: P, alpha: T1, beta: T2, gamma: T3) -> Self
+        where T1: Into<Parameter>,
+              T2: Into<Parameter>,
+              T3: Into<Parameter>
+    {
+        let n_features = projector.size();
+
+        SuccessorFeatures {
+            psi_func: Linear::new(projector, n_features),
+            reward_weights: Array1::zeros((n_features,)),
+
+            alpha: alpha.into(),
+            beta: beta.into(),
+            gamma: gamma.into(),
+        }
+    }
+
+    /// Returns the successor features ψ(s) for a given state.
+    pub fn successor_features(&self, s: &S::Repr) -> Array1<f64> {
+        let phi = self.psi_func.project(s);
+
+        self.successor_features_phi(&phi)
+    }
+
+    fn successor_features_phi(&self, phi: &Array1<f64>) -> Array1<f64> {
+        Array1::from_vec(QFunction::evaluate_phi(&self.psi_func, phi))
+    }
+
+    /// Evaluates the state under an alternative set of reward weights.
+    pub fn evaluate_with(&self, s: &S::Repr, reward_weights: &Array1<f64>) -> f64 {
+        self.successor_features(s).dot(reward_weights)
+    }
+
+    /// Replaces the learnt reward weights, e.g. to transfer to a new task.
+    pub fn set_reward_weights(&mut self, reward_weights: Array1<f64>) {
+        if reward_weights.len() != self.reward_weights.len() {
+            panic!("Expected {} reward weights, got {}.",
+                   self.reward_weights.len(),
+                   reward_weights.len());
+        }
+
+        self.reward_weights = reward_weights;
+    }
+}
+
+impl<S: Space, P: Projection<S>> Function<S::Repr, f64> for SuccessorFeatures<S, P> {
+    fn evaluate(&self, s: &S::Repr) -> f64 {
+        self.evaluate_with(s, &self.reward_weights)
+    }
+}
+
+impl<S: Space, P: Projection<S>> PredictionAgent<S> for SuccessorFeatures<S, P> {
+    fn handle_transition(&mut self, s: &S::Repr, ns: &S::Repr, r: f64) -> Option<f64> {
+        let phi_s = self.psi_func.project(s);
+        let phi_ns = self.psi_func.project(ns);
+
+        let psi_s = self.successor_features_phi(&phi_s);
+        let psi_ns = self.successor_features_phi(&phi_ns);
+
+        let td_error = r + self.gamma.value() * psi_ns.dot(&self.reward_weights) -
+                       psi_s.dot(&self.reward_weights);
+
+        // Successor features TD update:
+        let psi_errors = &phi_s + &(self.gamma.value() * &psi_ns) - &psi_s;
+        QFunction::update_phi(&mut self.psi_func,
+                              &phi_s,
+                              (self.alpha.value() * psi_errors).into_raw_vec());
+
+        // Reward weights regression:
+        let r_error = r - phi_s.dot(&self.reward_weights);
+        self.reward_weights.scaled_add(self.beta.value() * r_error, &phi_s);
+
+        tick!(Clock::Step => self.alpha, self.beta, self.gamma);
+        tick!(Clock::Update => self.alpha, self.beta, self.gamma);
+
+        Some(td_error)
+    }
+
+    fn handle_terminal(&mut self, _: &S::Repr) {
+        tick!(Clock::Episode => self.alpha, self.beta, self.gamma);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::SuccessorFeatures;
+    use agents::PredictionAgent;
+    use fa::Function;
+    use fa::projection::UniformGrid;
+    use geometry::RegularSpace;
+    use geometry::dimensions::{Continuous, Partitioned};
+    use ndarray::arr1;
+
+    type Chain = SuccessorFeatures<RegularSpace<Continuous>, UniformGrid>;
+
+    fn state(i: usize) -> Vec<f64> {
+        vec![i as f64 + 0.5]
+    }
+
+    /// Deterministic three state cycle, 0 -> 1 -> 2 -> 0, with a unit reward
+    /// on leaving state 2.
+    fn train() -> Chain {
+        let grid = UniformGrid::new(RegularSpace::new().push(Partitioned::new(0.0, 3.0, 3)));
+        let mut agent: Chain = SuccessorFeatures::new(grid, 0.5, 0.5, 0.5);
+
+        for i in 0..3000 {
+            let (s, ns) = (i % 3, (i + 1) % 3);
+
+            agent.handle_transition(&state(s), &state(ns), if s == 2 { 1.0 } else { 0.0 });
+        }
+
+        agent
+    }
+
+    #[test]
+    fn test_successor_features() {
+        let agent = train();
+
+        // ψ(s) = Σ_k γ^k φ(s_k) = [1, γ, γ^2] / (1 - γ^3), rotated by state:
+        let expected = [arr1(&[8.0, 4.0, 2.0]) / 7.0,
+                        arr1(&[2.0, 8.0, 4.0]) / 7.0,
+                        arr1(&[4.0, 2.0, 8.0]) / 7.0];
+
+        for (i, psi) in expected.iter().enumerate() {
+            assert!(agent.successor_features(&state(i)).all_close(psi, 1e-6));
+        }
+
+        assert!(agent.reward_weights.all_close(&arr1(&[0.0, 0.0, 1.0]), 1e-6));
+        assert!((agent.evaluate(&state(0)) - 2.0 / 7.0).abs() < 1e-6);
+        assert!((agent.evaluate(&state(2)) - 8.0 / 7.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_transfer() {
+        let mut agent = train();
+
+        agent.set_reward_weights(arr1(&[1.0, 0.0, 0.0]));
+
+        assert!((agent.evaluate(&state(0)) - 8.0 / 7.0).abs() < 1e-6);
+        assert!((agent.evaluate(&state(1)) - 2.0 / 7.0).abs() < 1e-6);
+        assert!((agent.evaluate(&state(2)) - 4.0 / 7.0).abs() < 1e-6);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_wrong_reward_weights() {
+        train().set_reward_weights(arr1(&[1.0, 0.0]));
+    }
+}
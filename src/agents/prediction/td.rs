@@ -1,5 +1,5 @@
-use Parameter;
-use agents::PredictionAgent;
+use {Parameter, Clock};
+use agents::{PredictionAgent, StepSize};
 use agents::memory::Trace;
 use fa::{VFunction, Projection, Linear};
 use geometry::Space;
@@ -7,6 +7,7 @@ use geometry::Space;
 use std::marker::PhantomData;
 
 
+#[derive(Serialize, Deserialize)]
 pub struct TD<S: Space, V: VFunction<S>> {
     pub v_func: V,
 
@@ -41,31 +42,34 @@ impl<S: Space, V> PredictionAgent<S> for TD<S, V>
         let v = self.v_func.evaluate(s);
         let nv = self.v_func.evaluate(ns);
 
-        let td_error = r + self.gamma * nv - v;
-        self.v_func.update(&s, self.alpha * td_error);
+        let td_error = r + self.gamma.value() * nv - v;
+        self.v_func.update(&s, self.alpha.value() * td_error);
+
+        tick!(Clock::Step => self.alpha, self.gamma);
+        tick!(Clock::Update => self.alpha, self.gamma);
 
         Some(td_error)
     }
 
     fn handle_terminal(&mut self, _: &S::Repr) {
-        self.alpha = self.alpha.step();
-        self.gamma = self.gamma.step();
+        tick!(Clock::Episode => self.alpha, self.gamma);
     }
 }
 
 
+#[derive(Serialize, Deserialize)]
 pub struct TDLambda<S: Space, P: Projection<S>> {
     trace: Trace,
 
     pub v_func: Linear<S, P>,
 
-    pub alpha: Parameter,
+    pub alpha: StepSize,
     pub gamma: Parameter,
 }
 
 impl<S: Space, P: Projection<S>> TDLambda<S, P> {
     pub fn new<T1, T2>(trace: Trace, v_func: Linear<S, P>, alpha: T1, gamma: T2) -> Self
-        where T1: Into<Parameter>,
+        where T1: Into<StepSize>,
               T2: Into<Parameter>
     {
         TDLambda {
@@ -87,17 +91,25 @@ impl<S: Space, P: Projection<S>> PredictionAgent<S> for TDLambda<S, P> {
         self.trace.decay(self.gamma.value());
         self.trace.update(&phi_s);
 
-        let td_error = r + self.gamma * self.v_func.evaluate_phi(&phi_ns) -
+        let td_error = r + self.gamma.value() * self.v_func.evaluate_phi(&phi_ns) -
                        self.v_func.evaluate_phi(&phi_s);
 
-        self.v_func.update_phi(self.trace.get(), self.alpha * td_error);
+        let dphi = self.gamma.value() * &phi_ns - &phi_s;
+        let increment = self.alpha.increment(&phi_s, self.trace.get(), &dphi, td_error);
+
+        self.v_func.update_phi(&increment, 1.0);
+
+        self.alpha.tick(Clock::Step);
+        self.alpha.tick(Clock::Update);
+        tick!(Clock::Step => self.gamma);
+        tick!(Clock::Update => self.gamma);
 
         Some(td_error)
     }
 
     fn handle_terminal(&mut self, _: &S::Repr) {
-        self.alpha = self.alpha.step();
-        self.gamma = self.gamma.step();
+        self.alpha.tick(Clock::Episode);
+        tick!(Clock::Episode => self.gamma);
 
         self.trace.decay(0.0);
     }
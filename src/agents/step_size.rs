@@ -0,0 +1,293 @@
+use {Parameter, Clock};
+use ndarray::Array1;
+
+
+/// Step size (learning rate) schedule for linear learners.
+///
+/// Besides a global `Parameter`, each feature may be given its own step size
+/// that is meta-learned online. The per-feature vectors are sized lazily on
+/// the first update.
+///
+/// Adaptation requires access to the feature vectors, so only the agents that
+/// update through them accept a `StepSize`: `TDLambda`, `GTD2`, `TDC`,
+/// `SARSALambda` and `GreedyGQ`. The one-step agents (`TD`, `SARSA`,
+/// `QLearning`, `ExpectedSARSA`, ...) remain generic over any `QFunction` and
+/// take a plain `Parameter`; `SARSALambda` with a `Trace::Null` reduces to
+/// one-step SARSA for linear function approximators.
+#[derive(Clone, Serialize, Deserialize)]
+pub enum StepSize {
+    Fixed(Parameter),
+
+    /// Incremental delta-bar-delta, with step sizes `exp(beta)` adapted by the
+    /// meta step size `theta`.
+    ///
+    /// Sutton, Richard S. "Adapting bias by gradient descent: An incremental
+    /// version of delta-bar-delta." AAAI. 1992.
+    ///
+    /// Kearney, Alex, et al. "TIDBD: Adapting Temporal-difference Step-sizes
+    /// Through Stochastic Meta-descent." arXiv preprint arXiv:1804.03334
+    /// (2018).
+    IDBD {
+        theta: f64,
+        beta_init: f64,
+        beta: Array1<f64>,
+        h: Array1<f64>,
+    },
+
+    /// Tuning-free variant of IDBD that normalises the meta update and bounds
+    /// the effective step size.
+    ///
+    /// Mahmood, Ashique Rupam, et al. "Tuning-free step-size adaptation."
+    /// Acoustics, Speech and Signal Processing (ICASSP), 2012 IEEE
+    /// International Conference on. IEEE, 2012.
+    Autostep {
+        mu: f64,
+        tau: f64,
+        alpha_init: f64,
+        alpha: Array1<f64>,
+        h: Array1<f64>,
+        v: Array1<f64>,
+    },
+
+    /// A single step size that is shrunk whenever it would overshoot the TD
+    /// target.
+    ///
+    /// Dabney, William, and Andrew G. Barto. "Adaptive step-size for online
+    /// temporal difference learning." AAAI. 2012.
+    AlphaBound { alpha: f64 },
+}
+
+impl StepSize {
+    pub fn idbd(alpha: f64, theta: f64) -> Self {
+        StepSize::IDBD {
+            theta: theta,
+            beta_init: alpha.ln(),
+            beta: Array1::zeros((0,)),
+            h: Array1::zeros((0,)),
+        }
+    }
+
+    pub fn autostep(alpha: f64, mu: f64, tau: f64) -> Self {
+        StepSize::Autostep {
+            mu: mu,
+            tau: tau,
+            alpha_init: alpha,
+            alpha: Array1::zeros((0,)),
+            h: Array1::zeros((0,)),
+            v: Array1::zeros((0,)),
+        }
+    }
+
+    pub fn alpha_bound(alpha: f64) -> Self {
+        StepSize::AlphaBound { alpha: alpha }
+    }
+
+    /// Returns a scalar summary of the current step size(s); the mean over
+    /// features for per-feature methods.
+    pub fn value(&self) -> f64 {
+        match *self {
+            StepSize::Fixed(ref p) => p.value(),
+            StepSize::IDBD { beta_init, ref beta, .. } => match beta.len() {
+                0 => beta_init.exp(),
+                n => beta.mapv(f64::exp).scalar_sum() / n as f64,
+            },
+            StepSize::Autostep { alpha_init, ref alpha, .. } => match alpha.len() {
+                0 => alpha_init,
+                n => alpha.scalar_sum() / n as f64,
+            },
+            StepSize::AlphaBound { alpha } => alpha,
+        }
+    }
+
+    /// Adapts the step sizes and returns the weight increment `alpha * error * z`.
+    ///
+    /// Here `phi` is the feature vector of the current state, `z` the update
+    /// direction (e.g. an eligibility trace, or simply `phi`) and `dphi` the
+    /// TD feature difference `gamma * phi' - phi`.
+    pub fn increment(&mut self,
+                     phi: &Array1<f64>,
+                     z: &Array1<f64>,
+                     dphi: &Array1<f64>,
+                     error: f64)
+                     -> Array1<f64> {
+        let n = z.len();
+
+        match *self {
+            StepSize::Fixed(ref p) => p.value() * error * z,
+
+            StepSize::IDBD { theta, beta_init, ref mut beta, ref mut h } => {
+                if beta.len() != n {
+                    *beta = Array1::from_elem((n,), beta_init);
+                    *h = Array1::zeros((n,));
+                }
+
+                let mut inc = Array1::zeros((n,));
+
+                for i in 0..n {
+                    beta[i] += theta * error * z[i] * h[i];
+
+                    let alpha = beta[i].exp();
+                    inc[i] = alpha * error * z[i];
+
+                    h[i] = h[i] * (1.0 - alpha * phi[i] * z[i]).max(0.0) + inc[i];
+                }
+
+                inc
+            },
+
+            StepSize::Autostep { mu, tau, alpha_init, ref mut alpha, ref mut h, ref mut v } => {
+                if alpha.len() != n {
+                    *alpha = Array1::from_elem((n,), alpha_init);
+                    *h = Array1::zeros((n,));
+                    *v = Array1::zeros((n,));
+                }
+
+                for i in 0..n {
+                    let g = error * z[i] * h[i];
+
+                    let trace = v[i] + alpha[i] * (phi[i] * z[i]).abs() * (g.abs() - v[i]) / tau;
+                    v[i] = g.abs().max(trace);
+
+                    if v[i] != 0.0 {
+                        alpha[i] *= (mu * g / v[i]).exp();
+                    }
+                }
+
+                // Bound the effective step size to avoid overshooting:
+                let m = alpha.iter().zip(z.iter()).zip(dphi.iter())
+                    .fold(0.0, |acc, ((a, zi), di)| acc - a * zi * di)
+                    .max(1.0);
+                *alpha /= m;
+
+                let inc = &*alpha * z * error;
+
+                for i in 0..n {
+                    h[i] = h[i] * (1.0 - alpha[i] * phi[i] * z[i]).max(0.0) + inc[i];
+                }
+
+                inc
+            },
+
+            StepSize::AlphaBound { ref mut alpha } => {
+                let denom = z.dot(dphi).abs();
+
+                if denom > 0.0 {
+                    *alpha = alpha.min(1.0 / denom);
+                }
+
+                *alpha * error * z
+            },
+        }
+    }
+
+    /// Scales `v` elementwise by the current step size of each feature,
+    /// without adapting them.
+    pub fn scale(&self, v: &Array1<f64>) -> Array1<f64> {
+        match *self {
+            StepSize::Fixed(ref p) => p.value() * v,
+            StepSize::IDBD { beta_init, ref beta, .. } => match beta.len() {
+                n if n == v.len() => beta.mapv(f64::exp) * v,
+                _ => beta_init.exp() * v,
+            },
+            StepSize::Autostep { alpha_init, ref alpha, .. } => match alpha.len() {
+                n if n == v.len() => alpha * v,
+                _ => alpha_init * v,
+            },
+            StepSize::AlphaBound { alpha } => alpha * v,
+        }
+    }
+
+    /// Advances a `Fixed` schedule on the event `clock`; adaptive step sizes
+    /// are unaffected.
+    pub fn tick(&mut self, clock: Clock) {
+        if let StepSize::Fixed(ref mut p) = *self {
+            *p = p.tick(clock);
+        }
+    }
+}
+
+impl From<f64> for StepSize {
+    fn from(alpha: f64) -> StepSize {
+        StepSize::Fixed(alpha.into())
+    }
+}
+
+impl From<Parameter> for StepSize {
+    fn from(alpha: Parameter) -> StepSize {
+        StepSize::Fixed(alpha)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::StepSize;
+    use ndarray::arr1;
+
+    // Learns the weight of a single, always active feature in a contextual
+    // bandit with target 1 and returns the final weight.
+    fn learn(mut step_size: StepSize, n_steps: usize) -> (StepSize, f64) {
+        let phi = arr1(&[1.0]);
+        let dphi = arr1(&[-1.0]);
+        let mut w = 0.0;
+
+        for _ in 0..n_steps {
+            let error = 1.0 - w;
+
+            w += step_size.increment(&phi, &phi, &dphi, error)[0];
+        }
+
+        (step_size, w)
+    }
+
+    #[test]
+    fn test_fixed() {
+        let (ss, w) = learn(0.5.into(), 1);
+
+        assert_eq!(w, 0.5);
+        assert_eq!(ss.value(), 0.5);
+    }
+
+    #[test]
+    fn test_idbd() {
+        let (ss, w) = learn(StepSize::idbd(0.01, 0.01), 500);
+
+        assert!(ss.value() > 0.01);
+        assert!((w - 1.0).abs() < 1e-2);
+    }
+
+    #[test]
+    fn test_autostep() {
+        let (ss, w) = learn(StepSize::autostep(0.01, 0.01, 1e4), 500);
+
+        assert!(ss.value() > 0.01 && ss.value() <= 1.0);
+        assert!((w - 1.0).abs() < 1e-2);
+    }
+
+    #[test]
+    fn test_scale() {
+        let v = arr1(&[1.0, -2.0]);
+
+        assert_eq!(StepSize::from(0.5).scale(&v), arr1(&[0.5, -1.0]));
+        assert!(StepSize::idbd(0.5, 0.1).scale(&v).all_close(&arr1(&[0.5, -1.0]), 1e-12));
+
+        // Per-feature step sizes once adapted:
+        let mut ss = StepSize::autostep(0.5, 0.1, 1e4);
+        for _ in 0..2 {
+            ss.increment(&arr1(&[1.0, 0.0]), &arr1(&[1.0, 0.0]), &arr1(&[-1.0, 0.0]), 1.0);
+        }
+
+        let scaled = ss.scale(&v);
+
+        assert_eq!(scaled[1], -1.0);
+        assert!(scaled[0] != 0.5);
+    }
+
+    #[test]
+    fn test_alpha_bound() {
+        let (ss, w) = learn(StepSize::alpha_bound(10.0), 1);
+
+        assert_eq!(ss.value(), 1.0);
+        assert_eq!(w, 1.0);
+    }
+}
@@ -0,0 +1,320 @@
+//! Saving and restoring of agents, function approximators and policies.
+//!
+//! Any serialisable value can be checkpointed either as (human readable) JSON
+//! or in a compact binary encoding. Random number generators are not part of
+//! the saved state; they are reseeded from the thread-local generator when a
+//! value is loaded.
+use bincode;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json;
+use std::error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read, Write, BufReader, BufWriter};
+use std::path::Path;
+
+
+/// Encoding used to store a checkpoint.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    JSON,
+    Binary,
+}
+
+impl Format {
+    /// Infers the format from the extension of `path`: `.json` files are
+    /// stored as JSON, anything else in the binary format.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Format {
+        match path.as_ref().extension().and_then(|e| e.to_str()) {
+            Some("json") => Format::JSON,
+            _ => Format::Binary,
+        }
+    }
+}
+
+
+/// Failure to save or restore a checkpoint, wrapping the underlying I/O or
+/// encoding error.
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    JSON(serde_json::Error),
+    Binary(bincode::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Io(ref e) => write!(f, "I/O error: {}", e),
+            Error::JSON(ref e) => write!(f, "JSON error: {}", e),
+            Error::Binary(ref e) => write!(f, "binary encoding error: {}", e),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn description(&self) -> &str {
+        match *self {
+            Error::Io(_) => "I/O error",
+            Error::JSON(_) => "JSON error",
+            Error::Binary(_) => "binary encoding error",
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Error {
+        Error::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Error {
+        Error::JSON(e)
+    }
+}
+
+impl From<bincode::Error> for Error {
+    fn from(e: bincode::Error) -> Error {
+        Error::Binary(e)
+    }
+}
+
+pub type Result<T> = ::std::result::Result<T, Error>;
+
+
+/// Writes `value` to `writer` in the given format.
+pub fn save_to<T, W>(value: &T, writer: W, format: Format) -> Result<()>
+    where T: Serialize,
+          W: Write
+{
+    match format {
+        Format::JSON => serde_json::to_writer(writer, value)?,
+        Format::Binary => bincode::serialize_into(writer, value)?,
+    }
+
+    Ok(())
+}
+
+/// Reads a value of type `T` from `reader` in the given format.
+pub fn load_from<T, R>(reader: R, format: Format) -> Result<T>
+    where T: DeserializeOwned,
+          R: Read
+{
+    Ok(match format {
+        Format::JSON => serde_json::from_reader(reader)?,
+        Format::Binary => bincode::deserialize_from(reader)?,
+    })
+}
+
+/// Saves `value` to the file at `path`, with the format inferred from the
+/// file extension.
+pub fn save<T: Serialize, P: AsRef<Path>>(value: &T, path: P) -> Result<()> {
+    let format = Format::from_path(&path);
+    let mut writer = BufWriter::new(File::create(path)?);
+
+    save_to(value, &mut writer, format)?;
+    writer.flush()?;
+
+    Ok(())
+}
+
+/// Loads a value of type `T` from the file at `path`, with the format inferred
+/// from the file extension.
+pub fn load<T: DeserializeOwned, P: AsRef<Path>>(path: P) -> Result<T> {
+    let format = Format::from_path(&path);
+    let reader = BufReader::new(File::open(path)?);
+
+    load_from(reader, format)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Parameter;
+    use agents::{ControlAgent, PredictionAgent};
+    use agents::control::gtd::GreedyGQ;
+    use agents::control::td::QSigma;
+    use agents::memory::Trace;
+    use agents::prediction::gtd::TDC;
+    use domains::{Observation, Transition};
+    use fa::{Function, Parameterised, Table, Linear};
+    use fa::projection::UniformGrid;
+    use geometry::{RegularSpace, ActionSpace};
+    use geometry::dimensions::{Continuous, Partitioned};
+    use ndarray::arr1;
+    use policies::{Policy, EpsilonGreedy, Greedy};
+
+    type Grid = Linear<RegularSpace<Continuous>, UniformGrid>;
+
+    fn grid(n_outputs: usize) -> Grid {
+        Linear::new(UniformGrid::new(RegularSpace::new().push(Partitioned::new(0.0, 1.0, 4))),
+                    n_outputs)
+    }
+
+    // The next state only admits action 0, so that greedy bootstrapping is
+    // deterministic:
+    fn transition(from: f64, action: usize, reward: f64, to: f64)
+                  -> Transition<RegularSpace<Continuous>, ActionSpace> {
+        Transition {
+            from: Observation::Full {
+                state: vec![from],
+                actions: vec![0, 1],
+            },
+            action: action,
+            reward: reward,
+            to: Observation::Full {
+                state: vec![to],
+                actions: vec![0],
+            },
+        }
+    }
+
+    fn values<F: Function<Vec<f64>, Vec<f64>>>(f: &F) -> Vec<f64> {
+        (0..4).flat_map(|i| f.evaluate(&vec![0.125 + 0.25 * i as f64])).collect()
+    }
+
+    fn assert_close(xs: Vec<f64>, ys: Vec<f64>) {
+        assert!(xs.iter().zip(ys.iter()).all(|(x, y)| (x - y).abs() < 1e-12));
+    }
+
+    fn round_trip<T: Serialize + DeserializeOwned>(value: &T, format: Format) -> T {
+        let mut buffer = vec![];
+        save_to(value, &mut buffer, format).unwrap();
+
+        load_from(buffer.as_slice(), format).unwrap()
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(Format::from_path("agent.json"), Format::JSON);
+        assert_eq!(Format::from_path("agent.bin"), Format::Binary);
+        assert_eq!(Format::from_path("agent"), Format::Binary);
+    }
+
+    #[test]
+    fn test_parameter() {
+        let p = Parameter::exponential(1.0, 0.0, 0.5).step().step();
+
+        for format in vec![Format::JSON, Format::Binary] {
+            let q = round_trip(&p, format);
+
+            assert_eq!(q.value(), 0.25);
+            assert_eq!(q.step().value(), 0.125);
+        }
+    }
+
+    #[test]
+    fn test_trace() {
+        let mut trace = Trace::Accumulating {
+            lambda: 0.5.into(),
+            eligibility: arr1(&[0.0; 3]),
+        };
+        trace.update(&arr1(&[1.0, 0.0, 2.0]));
+
+        for format in vec![Format::JSON, Format::Binary] {
+            assert_eq!(round_trip(&trace, format).get(), &arr1(&[1.0, 0.0, 2.0]));
+        }
+    }
+
+    #[test]
+    fn test_table() {
+        let mut t = Table::<(usize, usize), f64>::with_default(1.0);
+        t.update(&(1, 2), 2.0);
+
+        for format in vec![Format::JSON, Format::Binary] {
+            let u = round_trip(&t, format);
+
+            assert_eq!(u.evaluate(&(1, 2)), 3.0);
+            assert_eq!(u.evaluate(&(0, 0)), 1.0);
+            assert_eq!(u.visits(&(1, 2)), 1);
+        }
+    }
+
+    #[test]
+    fn test_policy() {
+        let p = EpsilonGreedy::new(0.0);
+
+        for format in vec![Format::JSON, Format::Binary] {
+            let mut q = round_trip(&p, format);
+
+            assert_eq!(q.sample(&[0.0, 1.0, 0.5]), 1);
+        }
+    }
+
+    #[test]
+    fn test_tdc() {
+        let mut agent = TDC::new(Linear::new(grid(1), 1), Linear::new(grid(1), 1), 0.1, 0.05, 0.9);
+
+        for i in 0..20 {
+            agent.handle_transition(&vec![0.125 + 0.25 * (i % 3) as f64], &vec![0.625], 1.0);
+        }
+
+        for format in vec![Format::JSON, Format::Binary] {
+            let mut restored: TDC<_, Grid> = round_trip(&agent, format);
+
+            // Auxiliary weights are restored along with the value function:
+            assert_close(values(&restored.a_func), values(&agent.a_func));
+            assert_close(values(&restored.v_func), values(&agent.v_func));
+            assert!(values(&agent.a_func).iter().any(|w| *w != 0.0));
+
+            let e1 = agent.handle_transition(&vec![0.375], &vec![0.875], -1.0).unwrap();
+            let e2 = restored.handle_transition(&vec![0.375], &vec![0.875], -1.0).unwrap();
+
+            assert_eq!(e1, e2);
+            assert_close(values(&restored.a_func), values(&agent.a_func));
+            assert_close(values(&restored.v_func), values(&agent.v_func));
+        }
+    }
+
+    #[test]
+    fn test_greedy_gq() {
+        let mut agent = GreedyGQ::new(grid(2), Greedy, 0.1, 0.5, 0.9);
+
+        for i in 0..20 {
+            agent.handle_transition(&transition(0.125 + 0.25 * (i % 3) as f64, i % 2, 1.0, 0.625));
+        }
+
+        for format in vec![Format::JSON, Format::Binary] {
+            let mut restored: GreedyGQ<_, Grid, Greedy> = round_trip(&agent, format);
+
+            assert!(restored.w.all_close(&agent.w, 1e-12));
+            assert!(restored.w.iter().any(|w| *w != 0.0));
+
+            restored.handle_transition(&transition(0.375, 1, -1.0, 0.875));
+            agent.handle_transition(&transition(0.375, 1, -1.0, 0.875));
+
+            assert!(restored.w.all_close(&agent.w, 1e-12));
+            assert_close(values(&restored.q_func), values(&agent.q_func));
+        }
+    }
+
+    #[test]
+    fn test_q_sigma() {
+        let mut agent = QSigma::new(grid(2), Greedy, 0.5, 0.9, 0.5, 3);
+
+        // Two transitions are held in the backup, short of the three needed
+        // for an update:
+        agent.handle_transition(&transition(0.125, 0, 1.0, 0.375));
+        agent.handle_transition(&transition(0.375, 1, 2.0, 0.625));
+
+        assert_close(values(&agent.q_func), vec![0.0; 8]);
+
+        let restored: Vec<QSigma<_, Grid, Greedy>> = vec![
+            round_trip(&agent, Format::JSON),
+            round_trip(&agent, Format::Binary),
+        ];
+
+        // The first backed up transition is now learned from:
+        agent.handle_transition(&transition(0.625, 0, 3.0, 0.875));
+        assert!(values(&agent.q_func)[0] != 0.0);
+
+        for mut r in restored {
+            r.handle_transition(&transition(0.625, 0, 3.0, 0.875));
+
+            assert_close(values(&r.q_func), values(&agent.q_func));
+        }
+    }
+}
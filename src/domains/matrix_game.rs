@@ -0,0 +1,227 @@
+use super::{Observation, Transition, MultiAgentDomain};
+use geometry::{ActionSpace, PairSpace};
+use geometry::dimensions::Discrete;
+
+use ndarray::Array2;
+
+
+/// Two-player repeated matrix game.
+///
+/// Each agent observes the pair `(own previous action, opponent's previous
+/// action)`, where the value `n_actions` denotes that no round has yet been
+/// played. The game terminates after a fixed number of rounds.
+pub struct MatrixGame {
+    payoffs: [Array2<f64>; 2],
+
+    n_rounds: usize,
+    round: usize,
+    last_actions: Option<(usize, usize)>,
+}
+
+impl MatrixGame {
+    pub fn new(row_payoffs: Array2<f64>, col_payoffs: Array2<f64>, n_rounds: usize) -> Self {
+        if row_payoffs.shape() != col_payoffs.shape() || row_payoffs.rows() != row_payoffs.cols() {
+            panic!("Payoff matrices must be square and of equal shape ({:?} vs {:?}).",
+                   row_payoffs.shape(),
+                   col_payoffs.shape());
+        }
+
+        MatrixGame {
+            payoffs: [row_payoffs, col_payoffs],
+
+            n_rounds: n_rounds,
+            round: 0,
+            last_actions: None,
+        }
+    }
+
+    /// The iterated prisoner's dilemma with actions `[cooperate, defect]`.
+    pub fn prisoners_dilemma(n_rounds: usize) -> Self {
+        let row = array![[-1.0, -3.0], [0.0, -2.0]];
+
+        MatrixGame::new(row.clone(), row.t().to_owned(), n_rounds)
+    }
+
+    /// Zero-sum matching pennies with actions `[heads, tails]`.
+    pub fn matching_pennies(n_rounds: usize) -> Self {
+        let row = array![[1.0, -1.0], [-1.0, 1.0]];
+
+        MatrixGame::new(row.clone(), -row, n_rounds)
+    }
+
+    fn n_actions(&self) -> usize {
+        self.payoffs[0].rows()
+    }
+
+    fn observe(&self, agent: usize) -> (usize, usize) {
+        match self.last_actions {
+            Some((a0, a1)) if agent == 0 => (a0, a1),
+            Some((a0, a1)) => (a1, a0),
+            None => (self.n_actions(), self.n_actions()),
+        }
+    }
+}
+
+impl MultiAgentDomain for MatrixGame {
+    type StateSpace = PairSpace<Discrete, Discrete>;
+    type ActionSpace = ActionSpace;
+
+    fn n_agents(&self) -> usize {
+        2
+    }
+
+    fn emit(&self) -> Vec<Observation<Self::StateSpace, Self::ActionSpace>> {
+        (0..2).map(|i| {
+            let s = self.observe(i);
+
+            if self.is_terminal() {
+                Observation::Terminal(s)
+            } else {
+                Observation::Full {
+                    state: s,
+                    actions: (0..self.n_actions()).collect(),
+                }
+            }
+        }).collect()
+    }
+
+    fn step(&mut self, actions: &[usize]) -> Vec<Transition<Self::StateSpace, Self::ActionSpace>> {
+        let from = self.emit();
+
+        self.round += 1;
+        self.last_actions = Some((actions[0], actions[1]));
+
+        let to = self.emit();
+        let rewards = self.rewards(&from, &to);
+
+        from.into_iter()
+            .zip(to.into_iter())
+            .zip(rewards.into_iter())
+            .enumerate()
+            .map(|(i, ((f, t), r))| {
+                Transition {
+                    from: f,
+                    action: actions[i],
+                    reward: r,
+                    to: t,
+                }
+            })
+            .collect()
+    }
+
+    fn is_terminal(&self) -> bool {
+        self.round >= self.n_rounds
+    }
+
+    fn rewards(&self,
+               _: &[Observation<Self::StateSpace, Self::ActionSpace>],
+               to: &[Observation<Self::StateSpace, Self::ActionSpace>])
+               -> Vec<f64> {
+        // Each agent observes (own action, opponent's action) for the round
+        // just played:
+        to.iter().enumerate().map(|(i, o)| {
+            let (own, other) = *o.state();
+
+            if own >= self.n_actions() {
+                0.0
+            } else if i == 0 {
+                self.payoffs[0][(own, other)]
+            } else {
+                self.payoffs[1][(other, own)]
+            }
+        }).collect()
+    }
+
+    fn state_space(&self) -> Self::StateSpace {
+        Self::StateSpace::new(Discrete::new(self.n_actions() + 1),
+                              Discrete::new(self.n_actions() + 1))
+    }
+
+    fn action_space(&self) -> ActionSpace {
+        ActionSpace::new(Discrete::new(self.n_actions()))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use domains::{Observation, MultiAgentDomain};
+
+    #[test]
+    fn test_initial_observation() {
+        let g = MatrixGame::prisoners_dilemma(10);
+        let obs = g.emit();
+
+        assert_eq!(obs.len(), 2);
+
+        for o in obs.iter() {
+            match o {
+                &Observation::Full { ref state, ref actions } => {
+                    assert_eq!(*state, (2, 2));
+                    assert_eq!(*actions, vec![0, 1]);
+                }
+                _ => panic!("Should yield a fully observable state."),
+            }
+        }
+    }
+
+    #[test]
+    fn test_payoffs() {
+        let mut g = MatrixGame::prisoners_dilemma(10);
+
+        let ts = g.step(&[0, 1]);
+        assert_eq!(ts[0].reward, -3.0);
+        assert_eq!(ts[1].reward, 0.0);
+
+        assert_eq!(*ts[0].to.state(), (0, 1));
+        assert_eq!(*ts[1].to.state(), (1, 0));
+
+        let ts = g.step(&[1, 1]);
+        assert_eq!(ts[0].reward, -2.0);
+        assert_eq!(ts[1].reward, -2.0);
+    }
+
+    #[test]
+    fn test_rewards_from_observations() {
+        let mut g = MatrixGame::prisoners_dilemma(10);
+
+        let first = g.step(&[0, 1]);
+        g.step(&[1, 1]);
+
+        // Rewards are computed from the observations given, not the latest
+        // round:
+        let (from, to): (Vec<_>, Vec<_>) = first.into_iter().map(|t| (t.from, t.to)).unzip();
+
+        assert_eq!(g.rewards(&from, &to), vec![-3.0, 0.0]);
+        assert_eq!(g.rewards(&from, &from), vec![0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_zero_sum() {
+        let mut g = MatrixGame::matching_pennies(100);
+
+        for i in 0..100 {
+            let ts = g.step(&[i % 2, (i / 2) % 2]);
+
+            assert_eq!(ts[0].reward + ts[1].reward, 0.0);
+        }
+    }
+
+    #[test]
+    fn test_is_terminal() {
+        let mut g = MatrixGame::prisoners_dilemma(3);
+
+        for _ in 0..3 {
+            assert!(!g.is_terminal());
+            g.step(&[0, 0]);
+        }
+
+        assert!(g.is_terminal());
+
+        match g.emit()[0] {
+            Observation::Terminal(_) => {},
+            _ => panic!("Should yield a terminal state."),
+        }
+    }
+}
@@ -1,6 +1,4 @@
 use geometry::Space;
-use geometry::dimensions;
-use geometry::dimensions::Dimension;
 
 
 pub enum Observation<S: Space, A: Space> {
@@ -25,6 +23,20 @@ impl<S: Space, A: Space> Observation<S, A> {
             &Terminal(ref state) => state,
         }
     }
+
+    /// Returns the set of legal actions; empty for terminal observations.
+    ///
+    /// An empty set places no restriction on the actions available, which is
+    /// also how domains without action masking should be represented.
+    pub fn actions(&self) -> &[A::Repr] {
+        use self::Observation::*;
+
+        match self {
+            &Full { ref actions, .. } |
+            &Partial { ref actions, .. } => actions,
+            &Terminal(_) => &[],
+        }
+    }
 }
 
 
@@ -42,7 +54,7 @@ pub trait Domain {
 
     fn emit(&self) -> Observation<Self::StateSpace, Self::ActionSpace>;
     fn step(&mut self,
-            a: <dimensions::Discrete as Dimension>::Value)
+            a: <Self::ActionSpace as Space>::Repr)
             -> Transition<Self::StateSpace, Self::ActionSpace>;
 
     fn is_terminal(&self) -> bool;
@@ -56,6 +68,32 @@ pub trait Domain {
 }
 
 
+/// Domain with multiple agents acting simultaneously.
+///
+/// Each agent receives its own observation and reward; a joint step therefore
+/// yields one `Transition` per agent, in the same order as the joint action.
+pub trait MultiAgentDomain {
+    type StateSpace: Space;
+    type ActionSpace: Space;
+
+    fn n_agents(&self) -> usize;
+
+    fn emit(&self) -> Vec<Observation<Self::StateSpace, Self::ActionSpace>>;
+    fn step(&mut self,
+            actions: &[<Self::ActionSpace as Space>::Repr])
+            -> Vec<Transition<Self::StateSpace, Self::ActionSpace>>;
+
+    fn is_terminal(&self) -> bool;
+    fn rewards(&self,
+               from: &[Observation<Self::StateSpace, Self::ActionSpace>],
+               to: &[Observation<Self::StateSpace, Self::ActionSpace>])
+               -> Vec<f64>;
+
+    fn state_space(&self) -> Self::StateSpace;
+    fn action_space(&self) -> Self::ActionSpace;
+}
+
+
 mod ode;
 use self::ode::*;
 
@@ -71,3 +109,9 @@ pub use self::acrobat::Acrobat;
 mod grid_world;
 mod cliff_walk;
 pub use self::cliff_walk::CliffWalk;
+
+mod matrix_game;
+pub use self::matrix_game::MatrixGame;
+
+mod pursuit;
+pub use self::pursuit::Pursuit;
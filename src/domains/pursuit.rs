@@ -0,0 +1,192 @@
+use super::{Observation, Transition, MultiAgentDomain};
+use super::grid_world::{Motion, GridWorld};
+use geometry::{ActionSpace, RegularSpace};
+use geometry::dimensions::Discrete;
+
+use ndarray::Array2;
+use rand::{Rng, thread_rng, ThreadRng};
+
+
+const ALL_ACTIONS: [Motion; 5] =
+    [Motion::North(1), Motion::East(1), Motion::South(1), Motion::West(1), Motion::North(0)];
+
+const REWARD_STEP: f64 = -1.0;
+const REWARD_CAPTURE: f64 = 10.0;
+
+
+/// Predator-prey pursuit gridworld.
+///
+/// A team of predators must capture a randomly moving prey on a square grid.
+/// Each predator observes `[x, y, prey_x, prey_y]` and the episode terminates
+/// as soon as any predator occupies the same cell as the prey.
+pub struct Pursuit {
+    gw: GridWorld<u8>,
+
+    predators: Vec<(usize, usize)>,
+    prey: (usize, usize),
+
+    rng: ThreadRng,
+}
+
+impl Pursuit {
+    pub fn new(size: usize, n_predators: usize) -> Pursuit {
+        let corners = [(0, 0), (size - 1, size - 1), (0, size - 1), (size - 1, 0)];
+
+        Pursuit {
+            gw: GridWorld::new(Array2::<u8>::zeros((size, size))),
+
+            predators: (0..n_predators).map(|i| corners[i % 4]).collect(),
+            prey: (size / 2, size / 2),
+
+            rng: thread_rng(),
+        }
+    }
+
+    fn observe(&self, agent: usize) -> Vec<usize> {
+        let loc = self.predators[agent];
+
+        vec![loc.0, loc.1, self.prey.0, self.prey.1]
+    }
+
+    fn update_state(&mut self, actions: &[usize]) {
+        for (loc, &a) in self.predators.iter_mut().zip(actions.iter()) {
+            *loc = self.gw.perform_motion(*loc, ALL_ACTIONS[a]);
+        }
+
+        // The prey only gets to move if it wasn't caught:
+        if !self.is_terminal() {
+            let motion = *self.rng.choose(&ALL_ACTIONS).unwrap();
+
+            self.prey = self.gw.perform_motion(self.prey, motion);
+        }
+    }
+}
+
+impl Default for Pursuit {
+    fn default() -> Pursuit {
+        Pursuit::new(7, 2)
+    }
+}
+
+impl MultiAgentDomain for Pursuit {
+    type StateSpace = RegularSpace<Discrete>;
+    type ActionSpace = ActionSpace;
+
+    fn n_agents(&self) -> usize {
+        self.predators.len()
+    }
+
+    fn emit(&self) -> Vec<Observation<Self::StateSpace, Self::ActionSpace>> {
+        (0..self.n_agents()).map(|i| {
+            let s = self.observe(i);
+
+            if self.is_terminal() {
+                Observation::Terminal(s)
+            } else {
+                Observation::Full {
+                    state: s,
+                    actions: vec![0, 1, 2, 3, 4],
+                }
+            }
+        }).collect()
+    }
+
+    fn step(&mut self, actions: &[usize]) -> Vec<Transition<Self::StateSpace, Self::ActionSpace>> {
+        let from = self.emit();
+
+        self.update_state(actions);
+        let to = self.emit();
+        let rewards = self.rewards(&from, &to);
+
+        from.into_iter()
+            .zip(to.into_iter())
+            .zip(rewards.into_iter())
+            .enumerate()
+            .map(|(i, ((f, t), r))| {
+                Transition {
+                    from: f,
+                    action: actions[i],
+                    reward: r,
+                    to: t,
+                }
+            })
+            .collect()
+    }
+
+    fn is_terminal(&self) -> bool {
+        self.predators.iter().any(|loc| *loc == self.prey)
+    }
+
+    fn rewards(&self,
+               _: &[Observation<Self::StateSpace, Self::ActionSpace>],
+               to: &[Observation<Self::StateSpace, Self::ActionSpace>])
+               -> Vec<f64> {
+        to.iter().map(|o| match o {
+            &Observation::Terminal(_) => REWARD_CAPTURE,
+            _ => REWARD_STEP,
+        }).collect()
+    }
+
+    fn state_space(&self) -> Self::StateSpace {
+        let (w, h) = (self.gw.width(), self.gw.height());
+
+        Self::StateSpace::new()
+            .push(Discrete::new(w))
+            .push(Discrete::new(h))
+            .push(Discrete::new(w))
+            .push(Discrete::new(h))
+    }
+
+    fn action_space(&self) -> ActionSpace {
+        ActionSpace::new(Discrete::new(5))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use domains::{Observation, MultiAgentDomain};
+
+    #[test]
+    fn test_initial_observation() {
+        let p = Pursuit::new(5, 2);
+        let obs = p.emit();
+
+        assert_eq!(p.n_agents(), 2);
+        assert_eq!(obs.len(), 2);
+
+        assert_eq!(*obs[0].state(), vec![0, 0, 2, 2]);
+        assert_eq!(*obs[1].state(), vec![4, 4, 2, 2]);
+    }
+
+    #[test]
+    fn test_capture() {
+        let mut p = Pursuit::new(5, 1);
+
+        p.predators[0] = (2, 1);
+        p.prey = (2, 2);
+
+        let ts = p.step(&[0]);
+
+        assert!(p.is_terminal());
+        assert_eq!(ts[0].reward, REWARD_CAPTURE);
+
+        match ts[0].to {
+            Observation::Terminal(_) => {},
+            _ => panic!("Should yield a terminal state."),
+        }
+    }
+
+    #[test]
+    fn test_step_reward() {
+        let mut p = Pursuit::new(5, 2);
+
+        let ts = p.step(&[4, 4]);
+
+        assert!(!p.is_terminal());
+        assert_eq!(ts.len(), 2);
+        assert_eq!(ts[0].reward, REWARD_STEP);
+        assert_eq!(ts[1].reward, REWARD_STEP);
+    }
+}
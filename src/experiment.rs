@@ -1,9 +1,9 @@
 
 
 use agents::ControlAgent;
-use domains::{Domain, Observation};
-use geometry::{Space, ActionSpace};
-use policies::Greedy;
+use domains::{Domain, MultiAgentDomain, Observation};
+use geometry::Space;
+use policies::Policy;
 use slog::{Record, Serializer, Result as LogResult, Logger, KV};
 
 
@@ -49,36 +49,44 @@ pub fn run<T>(runner: T, n_episodes: usize, logger: Option<Logger>) -> Vec<Episo
 
 
 /// Utility for running a single evaluation episode.
-pub struct Evaluation<'a, A: 'a, D> {
+///
+/// Actions are chosen by the agent under the given evaluation `policy`, e.g.
+/// `Greedy` for value-based agents over discrete actions.
+pub struct Evaluation<'a, A: 'a, D, P> {
     agent: &'a mut A,
     domain_factory: Box<Fn() -> D>,
 
-    greedy: Greedy,
+    policy: P,
 }
 
-impl<'a, S: Space, A, D> Evaluation<'a, A, D>
-    where A: ControlAgent<S, ActionSpace>,
-          D: Domain<StateSpace = S, ActionSpace = ActionSpace>
+impl<'a, S: Space, AS: Space, A, D, P> Evaluation<'a, A, D, P>
+    where A: ControlAgent<S, AS>,
+          D: Domain<StateSpace = S, ActionSpace = AS>,
+          P: Policy<AS>
 {
-    pub fn new(agent: &'a mut A, domain_factory: Box<Fn() -> D>) -> Evaluation<'a, A, D> {
+    pub fn new(agent: &'a mut A,
+               domain_factory: Box<Fn() -> D>,
+               policy: P)
+               -> Evaluation<'a, A, D, P> {
         Evaluation {
             agent: agent,
             domain_factory: domain_factory,
 
-            greedy: Greedy,
+            policy: policy,
         }
     }
 }
 
-impl<'a, S: Space, A, D> Iterator for Evaluation<'a, A, D>
-    where A: ControlAgent<S, ActionSpace>,
-          D: Domain<StateSpace = S, ActionSpace = ActionSpace>
+impl<'a, S: Space, AS: Space, A, D, P> Iterator for Evaluation<'a, A, D, P>
+    where A: ControlAgent<S, AS>,
+          D: Domain<StateSpace = S, ActionSpace = AS>,
+          P: Policy<AS>
 {
     type Item = Episode;
 
     fn next(&mut self) -> Option<Episode> {
         let mut domain = (self.domain_factory)();
-        let mut a = self.agent.evaluate_policy(&mut self.greedy, &domain.emit().state());
+        let mut a = self.agent.evaluate_policy(&mut self.policy, &domain.emit());
 
         let mut e = Episode {
             steps: 1,
@@ -96,7 +104,7 @@ impl<'a, S: Space, A, D> Iterator for Evaluation<'a, A, D>
                     self.agent.handle_terminal(s);
                     break;
                 }
-                _ => self.agent.evaluate_policy(&mut self.greedy, &t.to.state()),
+                _ => self.agent.evaluate_policy(&mut self.policy, &t.to),
             };
         }
 
@@ -113,9 +121,9 @@ pub struct SerialExperiment<'a, A: 'a, D> {
     step_limit: u64,
 }
 
-impl<'a, S: Space, A, D> SerialExperiment<'a, A, D>
-    where A: ControlAgent<S, ActionSpace>,
-          D: Domain<StateSpace = S, ActionSpace = ActionSpace>
+impl<'a, S: Space, AS: Space, A, D> SerialExperiment<'a, A, D>
+    where A: ControlAgent<S, AS>,
+          D: Domain<StateSpace = S, ActionSpace = AS>
 {
     pub fn new(agent: &'a mut A,
                domain_factory: Box<Fn() -> D>,
@@ -129,15 +137,15 @@ impl<'a, S: Space, A, D> SerialExperiment<'a, A, D>
     }
 }
 
-impl<'a, S: Space, A, D> Iterator for SerialExperiment<'a, A, D>
-    where A: ControlAgent<S, ActionSpace>,
-          D: Domain<StateSpace = S, ActionSpace = ActionSpace>
+impl<'a, S: Space, AS: Space, A, D> Iterator for SerialExperiment<'a, A, D>
+    where A: ControlAgent<S, AS>,
+          D: Domain<StateSpace = S, ActionSpace = AS>
 {
     type Item = Episode;
 
     fn next(&mut self) -> Option<Episode> {
         let mut domain = (self.domain_factory)();
-        let mut a = self.agent.pi(domain.emit().state());
+        let mut a = self.agent.pi(&domain.emit());
 
         let mut e = Episode {
             steps: 1,
@@ -162,10 +170,308 @@ impl<'a, S: Space, A, D> Iterator for SerialExperiment<'a, A, D>
                 break;
 
             } else {
-                a = self.agent.pi(&t.to.state());
+                a = self.agent.pi(&t.to);
             }
         }
 
         Some(e)
     }
 }
+
+
+/// Utility for running training episodes with independent learners on a
+/// multi-agent domain.
+pub struct MultiAgentExperiment<'a, A: 'a, D> {
+    agents: &'a mut Vec<A>,
+    domain_factory: Box<Fn() -> D>,
+
+    step_limit: u64,
+}
+
+impl<'a, S: Space, AS: Space, A, D> MultiAgentExperiment<'a, A, D>
+    where A: ControlAgent<S, AS>,
+          D: MultiAgentDomain<StateSpace = S, ActionSpace = AS>
+{
+    pub fn new(agents: &'a mut Vec<A>,
+               domain_factory: Box<Fn() -> D>,
+               step_limit: u64)
+               -> MultiAgentExperiment<'a, A, D> {
+        MultiAgentExperiment {
+            agents: agents,
+            domain_factory: domain_factory,
+            step_limit: step_limit,
+        }
+    }
+}
+
+impl<'a, S: Space, AS: Space, A, D> Iterator for MultiAgentExperiment<'a, A, D>
+    where A: ControlAgent<S, AS>,
+          D: MultiAgentDomain<StateSpace = S, ActionSpace = AS>
+{
+    type Item = Vec<Episode>;
+
+    fn next(&mut self) -> Option<Vec<Episode>> {
+        let mut domain = (self.domain_factory)();
+
+        if domain.n_agents() != self.agents.len() {
+            panic!("Domain expects {} agents, but {} were provided.",
+                   domain.n_agents(),
+                   self.agents.len());
+        }
+
+        let mut actions: Vec<AS::Repr> = domain.emit().iter()
+            .zip(self.agents.iter_mut())
+            .map(|(o, agent)| agent.pi(o))
+            .collect();
+
+        let mut es: Vec<Episode> = (0..self.agents.len()).map(|_| Episode {
+            steps: 1,
+            reward: 0.0,
+        }).collect();
+
+        for j in 1..(self.step_limit + 1) {
+            let ts = domain.step(&actions);
+            let is_last = domain.is_terminal() || j >= self.step_limit;
+
+            for (i, (t, agent)) in ts.iter().zip(self.agents.iter_mut()).enumerate() {
+                es[i].steps = j;
+                es[i].reward += t.reward;
+
+                agent.handle_transition(t);
+
+                if is_last {
+                    agent.handle_terminal(t.to.state());
+                } else {
+                    actions[i] = agent.pi(&t.to);
+                }
+            }
+
+            if is_last {
+                break;
+            }
+        }
+
+        Some(es)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{run, Episode, Evaluation, MultiAgentExperiment, SerialExperiment};
+    use agents::ControlAgent;
+    use domains::{Domain, MatrixGame, Observation, Transition};
+    use geometry::{ActionSpace, PairSpace, RegularSpace, UnitarySpace};
+    use geometry::dimensions::{Continuous, Discrete};
+    use policies::{Policy, Gaussian};
+
+    type Scalar = UnitarySpace<Continuous>;
+
+    // Three steps of choosing a real-valued action, rewarded by its distance
+    // from 1.0:
+    struct Target {
+        t: usize,
+    }
+
+    impl Target {
+        fn new() -> Target {
+            Target { t: 0 }
+        }
+    }
+
+    impl Domain for Target {
+        type StateSpace = RegularSpace<Continuous>;
+        type ActionSpace = Scalar;
+
+        fn emit(&self) -> Observation<Self::StateSpace, Scalar> {
+            if self.is_terminal() {
+                Observation::Terminal(vec![self.t as f64])
+            } else {
+                Observation::Full {
+                    state: vec![self.t as f64],
+                    actions: vec![],
+                }
+            }
+        }
+
+        fn step(&mut self, a: f64) -> Transition<Self::StateSpace, Scalar> {
+            let from = self.emit();
+
+            self.t += 1;
+
+            Transition {
+                from: from,
+                action: a,
+                reward: -(a - 1.0) * (a - 1.0),
+                to: self.emit(),
+            }
+        }
+
+        fn is_terminal(&self) -> bool {
+            self.t >= 3
+        }
+
+        fn reward(&self,
+                  _: &Observation<Self::StateSpace, Scalar>,
+                  _: &Observation<Self::StateSpace, Scalar>)
+                  -> f64 {
+            0.0
+        }
+
+        fn state_space(&self) -> Self::StateSpace {
+            RegularSpace::new().push(Continuous::new(0.0, 3.0))
+        }
+
+        fn action_space(&self) -> Scalar {
+            UnitarySpace::new(Continuous::new(-10.0, 10.0))
+        }
+    }
+
+    // Acts around a fixed mean and records what it sees:
+    struct Recorder {
+        mean: f64,
+        policy: Gaussian,
+
+        actions: Vec<f64>,
+        n_episodes: usize,
+    }
+
+    impl ControlAgent<RegularSpace<Continuous>, Scalar> for Recorder {
+        fn pi(&mut self, _: &Observation<RegularSpace<Continuous>, Scalar>) -> f64 {
+            Policy::<Scalar>::sample(&mut self.policy, &[self.mean])
+        }
+
+        fn evaluate_policy<T: Policy<Scalar>>(&self,
+                                              p: &mut T,
+                                              _: &Observation<RegularSpace<Continuous>, Scalar>)
+                                              -> f64 {
+            p.sample(&[self.mean])
+        }
+
+        fn handle_transition(&mut self, t: &Transition<RegularSpace<Continuous>, Scalar>) {
+            self.actions.push(t.action);
+        }
+
+        fn handle_terminal(&mut self, _: &Vec<f64>) {
+            self.n_episodes += 1;
+        }
+    }
+
+    #[test]
+    fn test_continuous_actions() {
+        let mut agent = Recorder {
+            mean: 1.0,
+            policy: Gaussian::new(0.1),
+
+            actions: vec![],
+            n_episodes: 0,
+        };
+
+        let episodes = {
+            let e = SerialExperiment::new(&mut agent, Box::new(Target::new), 10);
+
+            run(e, 5, None)
+        };
+
+        assert_eq!(episodes.len(), 5);
+
+        for e in episodes {
+            assert_eq!(e.steps, 3);
+            assert!(e.reward < 0.0 && e.reward > -1.0);
+        }
+
+        assert_eq!(agent.n_episodes, 5);
+        assert_eq!(agent.actions.len(), 15);
+        assert!(agent.actions.iter().any(|a| *a != 1.0));
+
+        // Evaluating with a near-deterministic policy recovers the mean:
+        let e = Evaluation::new(&mut agent, Box::new(Target::new), Gaussian::new(1e-6))
+            .next()
+            .unwrap();
+
+        assert!(e.reward > -1e-9);
+    }
+
+    type Pair = PairSpace<Discrete, Discrete>;
+
+    // Always plays the same action and records its rewards:
+    struct Constant {
+        action: usize,
+
+        rewards: Vec<f64>,
+        n_episodes: usize,
+    }
+
+    impl Constant {
+        fn new(action: usize) -> Constant {
+            Constant {
+                action: action,
+
+                rewards: vec![],
+                n_episodes: 0,
+            }
+        }
+    }
+
+    impl ControlAgent<Pair, ActionSpace> for Constant {
+        fn pi(&mut self, _: &Observation<Pair, ActionSpace>) -> usize {
+            self.action
+        }
+
+        fn evaluate_policy<T: Policy>(&self,
+                                      _: &mut T,
+                                      _: &Observation<Pair, ActionSpace>)
+                                      -> usize {
+            self.action
+        }
+
+        fn handle_transition(&mut self, t: &Transition<Pair, ActionSpace>) {
+            self.rewards.push(t.reward);
+        }
+
+        fn handle_terminal(&mut self, _: &(usize, usize)) {
+            self.n_episodes += 1;
+        }
+    }
+
+    #[test]
+    fn test_multi_agent() {
+        // The first agent always cooperates and the second always defects:
+        let mut agents = vec![Constant::new(0), Constant::new(1)];
+
+        let episodes: Vec<Vec<Episode>> = {
+            let e = MultiAgentExperiment::new(&mut agents,
+                                              Box::new(|| MatrixGame::prisoners_dilemma(3)),
+                                              10);
+
+            e.take(2).collect()
+        };
+
+        assert_eq!(episodes.len(), 2);
+
+        for es in episodes {
+            assert_eq!(es.len(), 2);
+
+            assert_eq!(es[0].steps, 3);
+            assert_eq!(es[0].reward, -9.0);
+
+            assert_eq!(es[1].steps, 3);
+            assert_eq!(es[1].reward, 0.0);
+        }
+
+        assert_eq!(agents[0].rewards, vec![-3.0; 6]);
+        assert_eq!(agents[1].rewards, vec![0.0; 6]);
+        assert!(agents.iter().all(|a| a.n_episodes == 2));
+
+        // Episodes are cut short at the step limit:
+        let es = MultiAgentExperiment::new(&mut agents,
+                                           Box::new(|| MatrixGame::prisoners_dilemma(3)),
+                                           2)
+            .next()
+            .unwrap();
+
+        assert_eq!(es[0].steps, 2);
+        assert_eq!(es[0].reward, -6.0);
+        assert!(agents.iter().all(|a| a.n_episodes == 3));
+    }
+}
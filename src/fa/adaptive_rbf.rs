@@ -0,0 +1,330 @@
+use super::{Function, Parameterised, VFunction, QFunction};
+use geometry::{Span, Space, RegularSpace};
+use geometry::dimensions::{Continuous, Partitioned};
+use ndarray::{Array1, Array2};
+use utils::cartesian_product;
+
+
+/// Configuration of the resource-allocating growth criterion.
+///
+/// A new centre is placed at the input whenever the magnitude of the (scaled)
+/// error exceeds `error_threshold` and the nearest existing centre is further
+/// than `distance_threshold` away. Its width is `overlap` times the distance to
+/// the nearest centre.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct Growth {
+    pub error_threshold: f64,
+    pub distance_threshold: f64,
+    pub overlap: f64,
+    pub max_centres: usize,
+}
+
+
+/// Gaussian radial basis function network with adaptive centres and widths.
+///
+/// The linear output weights, centres and (per-dimension) widths are all
+/// updated by gradient descent on the squared error. Optionally, the network
+/// grows by allocating new centres in poorly represented regions of the input
+/// space.
+///
+/// Platt, John. "A resource-allocating network for function interpolation."
+/// Neural computation 3.2 (1991): 213-225.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct AdaptiveRBFNetwork {
+    pub mu: Array2<f64>,
+    pub sigma: Array2<f64>,
+    pub weights: Array2<f64>,
+
+    pub centre_rate: f64,
+    pub width_rate: f64,
+
+    growth: Option<Growth>,
+}
+
+impl AdaptiveRBFNetwork {
+    pub fn new(mu: Array2<f64>, sigma: Array2<f64>, n_outputs: usize) -> Self {
+        if mu.shape() != sigma.shape() {
+            panic!("Dimensions of mu ({:?}) and sigma ({:?}) must agree.",
+                   mu.shape(),
+                   sigma.shape());
+        }
+
+        let n_centres = mu.rows();
+
+        AdaptiveRBFNetwork {
+            mu: mu,
+            sigma: sigma,
+            weights: Array2::zeros((n_centres, n_outputs)),
+
+            centre_rate: 1.0,
+            width_rate: 1.0,
+
+            growth: None,
+        }
+    }
+
+    /// Constructs a network without any centres, to be grown online.
+    pub fn empty(n_dims: usize, n_outputs: usize, growth: Growth) -> Self {
+        AdaptiveRBFNetwork::new(Array2::zeros((0, n_dims)), Array2::zeros((0, n_dims)), n_outputs)
+            .with_growth(growth)
+    }
+
+    pub fn from_space(input_space: RegularSpace<Partitioned>, n_outputs: usize) -> Self {
+        let n_features = match input_space.span() {
+            Span::Finite(s) => s,
+            _ => { panic!("`AdaptiveRBFNetwork` only supports partitioned input spaces.") }
+        };
+
+        let n_dims = input_space.dim();
+        let centres = input_space.centres();
+        let flat_combs = cartesian_product(&centres)
+            .iter()
+            .cloned()
+            .flat_map(|e| e)
+            .collect();
+
+        let mu = Array2::from_shape_vec((n_features, n_dims), flat_combs).unwrap();
+        let widths: Vec<f64> = input_space.iter().map(|d| d.partition_width().abs()).collect();
+        let sigma = Array2::from_shape_fn((n_features, n_dims), |(_, j)| widths[j]);
+
+        AdaptiveRBFNetwork::new(mu, sigma, n_outputs)
+    }
+
+    /// Sets the learning rates of the centres and widths relative to that of
+    /// the output weights.
+    pub fn with_learning_rates(mut self, centre_rate: f64, width_rate: f64) -> Self {
+        self.centre_rate = centre_rate;
+        self.width_rate = width_rate;
+        self
+    }
+
+    pub fn with_growth(mut self, growth: Growth) -> Self {
+        self.growth = Some(growth);
+        self
+    }
+
+    pub fn n_centres(&self) -> usize {
+        self.mu.rows()
+    }
+
+    pub fn kernel(&self, input: &[f64]) -> Array1<f64> {
+        Array1::from_shape_fn((self.n_centres(),), |i| {
+            let exponent = input.iter().enumerate().fold(0.0, |acc, (d, x)| {
+                let z = (x - self.mu[(i, d)]) / self.sigma[(i, d)];
+
+                acc + z * z
+            });
+
+            (-0.5 * exponent).exp()
+        })
+    }
+
+    fn nearest(&self, input: &[f64]) -> Option<f64> {
+        self.mu.outer_iter().map(|m| {
+            m.iter().zip(input.iter()).fold(0.0, |acc, (c, x)| acc + (x - c) * (x - c)).sqrt()
+        }).fold(None, |acc: Option<f64>, d| Some(acc.map_or(d, |a| a.min(d))))
+    }
+
+    fn grow(&mut self, input: &[f64], errors: &[f64]) -> bool {
+        let growth = match self.growth {
+            Some(g) => g,
+            None => return false,
+        };
+
+        let max_error = errors.iter().fold(0.0f64, |acc, e| acc.max(e.abs()));
+        if max_error <= growth.error_threshold || self.n_centres() >= growth.max_centres {
+            return false;
+        }
+
+        let dist = self.nearest(input).unwrap_or(::std::f64::INFINITY);
+        if dist <= growth.distance_threshold {
+            return false;
+        }
+
+        let width = growth.overlap * if dist.is_finite() { dist } else { growth.distance_threshold };
+
+        let (n, d, k) = (self.n_centres(), self.mu.cols(), self.weights.cols());
+
+        self.mu = Array2::from_shape_fn((n + 1, d), |(i, j)| {
+            if i < n { self.mu[(i, j)] } else { input[j] }
+        });
+        self.sigma = Array2::from_shape_fn((n + 1, d), |(i, j)| {
+            if i < n { self.sigma[(i, j)] } else { width }
+        });
+        self.weights = Array2::from_shape_fn((n + 1, k), |(i, j)| {
+            if i < n { self.weights[(i, j)] } else { errors.get(j).cloned().unwrap_or(0.0) }
+        });
+
+        true
+    }
+
+    fn update_all(&mut self, input: &[f64], errors: &[f64]) {
+        if self.grow(input, errors) {
+            return;
+        }
+
+        let phi = self.kernel(input);
+
+        // Backpropagated error at each centre, computed with the old weights:
+        let mut g = Array1::zeros((self.n_centres(),));
+        for (c, e) in errors.iter().enumerate() {
+            g.scaled_add(*e, &self.weights.column(c));
+        }
+        g *= &phi;
+
+        for (i, gi) in g.iter().enumerate() {
+            for (d, x) in input.iter().enumerate() {
+                let (m, s) = (self.mu[(i, d)], self.sigma[(i, d)]);
+                let diff = x - m;
+
+                self.mu[(i, d)] += self.centre_rate * gi * diff / (s * s);
+                self.sigma[(i, d)] += self.width_rate * gi * diff * diff / (s * s * s);
+            }
+        }
+
+        for (c, e) in errors.iter().enumerate() {
+            self.weights.column_mut(c).scaled_add(*e, &phi);
+        }
+    }
+}
+
+impl Function<Vec<f64>, f64> for AdaptiveRBFNetwork {
+    fn evaluate(&self, input: &Vec<f64>) -> f64 {
+        self.kernel(input).dot(&self.weights.column(0))
+    }
+}
+
+impl Function<Vec<f64>, Vec<f64>> for AdaptiveRBFNetwork {
+    fn evaluate(&self, input: &Vec<f64>) -> Vec<f64> {
+        let phi = self.kernel(input);
+
+        self.weights.t().dot(&phi).into_raw_vec()
+    }
+}
+
+impl Parameterised<Vec<f64>, f64> for AdaptiveRBFNetwork {
+    fn update(&mut self, input: &Vec<f64>, error: f64) {
+        self.update_all(input, &[error]);
+    }
+}
+
+impl Parameterised<Vec<f64>, Vec<f64>> for AdaptiveRBFNetwork {
+    fn update(&mut self, input: &Vec<f64>, errors: Vec<f64>) {
+        self.update_all(input, &errors);
+    }
+}
+
+impl VFunction<RegularSpace<Continuous>> for AdaptiveRBFNetwork {}
+
+impl QFunction<RegularSpace<Continuous>> for AdaptiveRBFNetwork {
+    fn evaluate_action(&self, input: &Vec<f64>, action: usize) -> f64 {
+        self.kernel(input).dot(&self.weights.column(action))
+    }
+
+    fn update_action(&mut self, input: &Vec<f64>, action: usize, error: f64) {
+        let mut errors = vec![0.0; self.weights.cols()];
+        errors[action] = error;
+
+        self.update_all(input, &errors);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::arr2;
+
+    fn value(rbf: &AdaptiveRBFNetwork, x: f64) -> f64 {
+        rbf.evaluate(&vec![x])
+    }
+
+    #[test]
+    fn test_kernel() {
+        let rbf = AdaptiveRBFNetwork::new(arr2(&[[0.0], [1.0]]), arr2(&[[1.0], [0.5]]), 1);
+        let phi = rbf.kernel(&[1.0]);
+
+        assert!((phi[0] - (-0.5f64).exp()).abs() < 1e-7);
+        assert_eq!(phi[1], 1.0);
+    }
+
+    #[test]
+    fn test_from_space() {
+        let space = RegularSpace::new()
+            .push(Partitioned::new(0.0, 1.0, 4))
+            .push(Partitioned::new(0.0, 2.0, 2));
+        let rbf = AdaptiveRBFNetwork::from_space(space, 3);
+
+        assert_eq!(rbf.n_centres(), 8);
+        assert_eq!(rbf.weights.shape(), &[8, 3]);
+        assert!(rbf.sigma.column(0).iter().all(|s| (s - 0.25).abs() < 1e-7));
+        assert!(rbf.sigma.column(1).iter().all(|s| (s - 1.0).abs() < 1e-7));
+    }
+
+    #[test]
+    fn test_centre_adaptation() {
+        let mut rbf = AdaptiveRBFNetwork::new(arr2(&[[0.0]]), arr2(&[[1.0]]), 1);
+        rbf.weights[(0, 0)] = 1.0;
+
+        // Positive errors to the right should pull the centre rightwards:
+        for _ in 0..10 {
+            rbf.update(&vec![0.5], 0.1);
+        }
+
+        assert!(rbf.mu[(0, 0)] > 0.0);
+        assert!(rbf.sigma[(0, 0)] > 1.0);
+    }
+
+    #[test]
+    fn test_regression() {
+        let mut rbf = AdaptiveRBFNetwork::new(arr2(&[[0.0], [1.0]]), arr2(&[[0.5], [0.5]]), 1)
+            .with_learning_rates(0.1, 0.1);
+
+        for _ in 0..1000 {
+            for &(x, y) in [(0.0, 1.0), (1.0, -1.0)].iter() {
+                let err = y - value(&rbf, x);
+
+                rbf.update(&vec![x], 0.1 * err);
+            }
+        }
+
+        assert!((value(&rbf, 0.0) - 1.0).abs() < 1e-2);
+        assert!((value(&rbf, 1.0) + 1.0).abs() < 1e-2);
+    }
+
+    #[test]
+    fn test_growth() {
+        let mut rbf = AdaptiveRBFNetwork::empty(1, 1, Growth {
+            error_threshold: 0.1,
+            distance_threshold: 0.5,
+            overlap: 1.0,
+            max_centres: 3,
+        });
+
+        rbf.update(&vec![0.0], 1.0);
+        assert_eq!(rbf.n_centres(), 1);
+        assert_eq!(value(&rbf, 0.0), 1.0);
+
+        // Too close to an existing centre:
+        rbf.update(&vec![0.2], 1.0);
+        assert_eq!(rbf.n_centres(), 1);
+
+        // Error too small:
+        rbf.update(&vec![5.0], 0.01);
+        assert_eq!(rbf.n_centres(), 1);
+
+        // The first centre was adapted by the previous updates, and the new
+        // width spans the distance to wherever it now lies:
+        rbf.update(&vec![5.0], 1.0);
+        assert_eq!(rbf.n_centres(), 2);
+        assert!((rbf.sigma[(1, 0)] - (5.0 - rbf.mu[(0, 0)])).abs() < 1e-10);
+
+        rbf.update(&vec![10.0], 1.0);
+        assert_eq!(rbf.n_centres(), 3);
+        assert_eq!(rbf.sigma[(2, 0)], 5.0);
+
+        // At capacity:
+        rbf.update(&vec![15.0], 1.0);
+        assert_eq!(rbf.n_centres(), 3);
+    }
+}
@@ -0,0 +1,251 @@
+use super::{Function, Projection};
+use geometry::Space;
+use ndarray::{ArrayView, Array1, Array2};
+use std::marker::PhantomData;
+
+
+/// Categorical return distribution with linear logits.
+///
+/// Each action is associated with a softmax distribution over a fixed, evenly
+/// spaced support of `n_atoms` values in `[v_min, v_max]`. The logits of the
+/// distribution are linear in the features of the underlying projection.
+#[derive(Serialize, Deserialize)]
+pub struct CategoricalLinear<S: Space, P: Projection<S>> {
+    pub projector: P,
+    pub weights: Vec<Array2<f64>>,
+    pub support: Array1<f64>,
+
+    phantom: PhantomData<S>,
+}
+
+impl<S: Space, P: Projection<S>> CategoricalLinear<S, P> {
+    pub fn new(projector: P, n_actions: usize, n_atoms: usize, v_min: f64, v_max: f64) -> Self {
+        if n_atoms < 2 {
+            panic!("A categorical distribution requires at least 2 atoms.");
+        }
+
+        let n_features = projector.size();
+
+        CategoricalLinear {
+            projector: projector,
+            weights: vec![Array2::<f64>::zeros((n_features, n_atoms)); n_actions],
+            support: Array1::linspace(v_min, v_max, n_atoms),
+
+            phantom: PhantomData,
+        }
+    }
+
+    pub fn n_actions(&self) -> usize {
+        self.weights.len()
+    }
+
+    pub fn n_atoms(&self) -> usize {
+        self.support.len()
+    }
+
+    /// Returns the probability mass on each atom for a given feature vector and action.
+    pub fn distribution_phi(&self, phi: &Array1<f64>, action: usize) -> Array1<f64> {
+        let logits = self.weights[action].t().dot(phi);
+        let max = logits.fold(::std::f64::MIN, |acc, v| acc.max(*v));
+
+        let ps = logits.mapv(|l| (l - max).exp());
+        let z = ps.scalar_sum();
+
+        ps / z
+    }
+
+    pub fn distribution(&self, input: &S::Repr, action: usize) -> Array1<f64> {
+        let phi = self.projector.project(input);
+
+        self.distribution_phi(&phi, action)
+    }
+
+    /// Returns the expected return of each action for a given feature vector.
+    pub fn expectations_phi(&self, phi: &Array1<f64>) -> Vec<f64> {
+        (0..self.n_actions())
+            .map(|a| self.distribution_phi(phi, a).dot(&self.support))
+            .collect()
+    }
+
+    /// Returns the conditional value at risk of the return distribution at the given level.
+    pub fn cvar_phi(&self, phi: &Array1<f64>, action: usize, level: f64) -> f64 {
+        if level <= 0.0 || level > 1.0 {
+            panic!("CVaR level must lie in (0, 1], got {}.", level);
+        }
+
+        let ps = self.distribution_phi(phi, action);
+
+        let mut mass = 0.0;
+        let mut acc = 0.0;
+
+        for (p, z) in ps.iter().zip(self.support.iter()) {
+            let m = p.min(level - mass);
+
+            acc += m * z;
+            mass += m;
+
+            if mass >= level {
+                break;
+            }
+        }
+
+        acc / level
+    }
+
+    pub fn cvar(&self, input: &S::Repr, action: usize, level: f64) -> f64 {
+        let phi = self.projector.project(input);
+
+        self.cvar_phi(&phi, action, level)
+    }
+
+    /// Projects the distributional Bellman target onto the support of the distribution.
+    pub fn project_target(&self, reward: f64, gamma: f64, next_dist: &Array1<f64>) -> Array1<f64> {
+        project_distribution(&self.support, next_dist, reward, gamma)
+    }
+
+    /// Performs a cross-entropy gradient step towards a target distribution.
+    pub fn update_distribution_phi(&mut self,
+                                   phi: &Array1<f64>,
+                                   action: usize,
+                                   target: &Array1<f64>,
+                                   alpha: f64) {
+        let errors = target - &self.distribution_phi(phi, action);
+
+        let n_atoms = self.n_atoms();
+        let w = &mut self.weights[action];
+
+        let phi_view = phi.view().into_shape((w.rows(), 1)).unwrap();
+        let error_matrix = ArrayView::from_shape((1, n_atoms), errors.as_slice().unwrap())
+            .unwrap();
+
+        w.scaled_add(alpha, &phi_view.dot(&error_matrix));
+    }
+}
+
+impl<S: Space, P: Projection<S>> Function<S::Repr, Vec<f64>> for CategoricalLinear<S, P> {
+    fn evaluate(&self, input: &S::Repr) -> Vec<f64> {
+        let phi = self.projector.project(input);
+
+        self.expectations_phi(&phi)
+    }
+}
+
+impl<S: Space, P: Projection<S>> Projection<S> for CategoricalLinear<S, P> {
+    fn project(&self, input: &S::Repr) -> Array1<f64> {
+        self.projector.project(input)
+    }
+
+    fn project_onto(&self, input: &S::Repr, phi: &mut Array1<f64>) {
+        self.projector.project_onto(input, phi);
+    }
+
+    fn dim(&self) -> usize {
+        self.projector.dim()
+    }
+
+    fn size(&self) -> usize {
+        self.projector.size()
+    }
+
+    fn equivalent(&self, other: &Self) -> bool {
+        self.projector.equivalent(&other.projector) && self.support == other.support &&
+        self.n_actions() == other.n_actions()
+    }
+}
+
+
+/// Projects the distribution of `reward + gamma*Z` onto the (evenly spaced) support of `Z`.
+pub fn project_distribution(support: &Array1<f64>,
+                            probabilities: &Array1<f64>,
+                            reward: f64,
+                            gamma: f64)
+                            -> Array1<f64> {
+    let n_atoms = support.len();
+
+    let v_min = support[0];
+    let v_max = support[n_atoms - 1];
+    let dz = (v_max - v_min) / (n_atoms - 1) as f64;
+
+    let mut m = Array1::zeros((n_atoms,));
+
+    for (z, p) in support.iter().zip(probabilities.iter()) {
+        let tz = clip!(v_min, reward + gamma * z, v_max);
+
+        // Rounding may otherwise push b beyond the last atom when tz = v_max:
+        let b = ((tz - v_min) / dz).max(0.0).min((n_atoms - 1) as f64);
+
+        let l = b.floor() as usize;
+        let u = b.ceil() as usize;
+
+        if l == u {
+            m[l] += *p;
+        } else {
+            m[l] += p * (u as f64 - b);
+            m[u] += p * (b - l as f64);
+        }
+    }
+
+    m
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::arr1;
+
+    #[test]
+    fn test_projection_identity() {
+        let support = Array1::linspace(-1.0, 1.0, 5);
+        let ps = arr1(&[0.1, 0.2, 0.4, 0.2, 0.1]);
+
+        assert!(project_distribution(&support, &ps, 0.0, 1.0).all_close(&ps, 1e-7));
+    }
+
+    #[test]
+    fn test_projection_shift() {
+        let support = Array1::linspace(-1.0, 1.0, 5);
+        let ps = arr1(&[0.0, 0.0, 1.0, 0.0, 0.0]);
+
+        let m = project_distribution(&support, &ps, 0.25, 1.0);
+
+        assert!(m.all_close(&arr1(&[0.0, 0.0, 0.5, 0.5, 0.0]), 1e-7));
+    }
+
+    #[test]
+    fn test_projection_clipping() {
+        let support = Array1::linspace(-1.0, 1.0, 5);
+        let ps = arr1(&[0.2; 5]);
+
+        let m = project_distribution(&support, &ps, 10.0, 0.9);
+
+        assert!(m.all_close(&arr1(&[0.0, 0.0, 0.0, 0.0, 1.0]), 1e-7));
+    }
+
+    #[test]
+    fn test_projection_conserves_mass() {
+        let support = Array1::linspace(-10.0, 10.0, 51);
+        let ps = Array1::from_elem((51,), 1.0 / 51.0);
+
+        for &r in [-3.3, -0.1, 0.0, 1.7, 4.2].iter() {
+            let m = project_distribution(&support, &ps, r, 0.95);
+
+            assert!((m.scalar_sum() - 1.0).abs() < 1e-7);
+        }
+    }
+
+    #[test]
+    fn test_projection_rounding_at_bounds() {
+        let support = Array1::linspace(-1.0, 1.0, 50);
+        let ps = Array1::from_elem((50,), 1.0 / 50.0);
+
+        let upper = project_distribution(&support, &ps, 10.0, 1.0);
+        assert!((upper[49] - 1.0).abs() < 1e-7);
+
+        let lower = project_distribution(&support, &ps, -10.0, 1.0);
+        assert!((lower[0] - 1.0).abs() < 1e-7);
+
+        let m = project_distribution(&support, &ps, 1.0, 1.0);
+        assert!((m.scalar_sum() - 1.0).abs() < 1e-7);
+    }
+}
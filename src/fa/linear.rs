@@ -1,6 +1,9 @@
-use super::{Function, Parameterised, VFunction, QFunction, Projection, SparseProjection};
+use super::{Function, Parameterised, VFunction, QFunction, LinearQFunction};
+use super::{Projection, SparseProjection, ActionProjection};
+use super::optim::{Optimiser, SGD};
 use geometry::Space;
-use ndarray::{ArrayView, Array1, Array2};
+use ndarray::{Array1, Array2};
+use std::collections::HashMap;
 use std::marker::PhantomData;
 use utils::dot;
 
@@ -9,27 +12,39 @@ pub type Linear<S, P> = DenseLinear<S, P>;
 
 
 #[derive(Serialize, Deserialize)]
-pub struct DenseLinear<S: Space, P: Projection<S>> {
+pub struct DenseLinear<S: Space, P: Projection<S>, O: Optimiser = SGD> {
     pub projector: P,
     pub weights: Array2<f64>,
+    pub optimiser: O,
 
     phantom: PhantomData<S>,
 }
 
 impl<S: Space, P: Projection<S>> DenseLinear<S, P> {
     pub fn new(projector: P, n_outputs: usize) -> Self {
+        DenseLinear::with_optimiser(projector, n_outputs, SGD::default())
+    }
+}
+
+impl<S: Space, P: Projection<S>, O: Optimiser> DenseLinear<S, P, O> {
+    pub fn with_optimiser(projector: P, n_outputs: usize, optimiser: O) -> Self {
         let n_features = projector.size();
 
-        Self {
+        DenseLinear {
             projector: projector,
             weights: Array2::<f64>::zeros((n_features, n_outputs)),
+            optimiser: optimiser,
 
             phantom: PhantomData,
         }
     }
+
+    fn step_column(&mut self, phi: &Array1<f64>, column: usize, error: f64) {
+        self.optimiser.step_column(&mut self.weights, column, phi, error);
+    }
 }
 
-impl<S: Space, P: Projection<S>> Function<S::Repr, f64> for DenseLinear<S, P> {
+impl<S: Space, P: Projection<S>, O: Optimiser> Function<S::Repr, f64> for DenseLinear<S, P, O> {
     fn evaluate(&self, input: &S::Repr) -> f64 {
         // Compute the feature vector phi:
         let phi = self.projector.project(input);
@@ -38,7 +53,8 @@ impl<S: Space, P: Projection<S>> Function<S::Repr, f64> for DenseLinear<S, P> {
     }
 }
 
-impl<S: Space, P: Projection<S>> Function<S::Repr, Vec<f64>> for DenseLinear<S, P> {
+impl<S: Space, P: Projection<S>, O: Optimiser> Function<S::Repr, Vec<f64>>
+    for DenseLinear<S, P, O> {
     fn evaluate(&self, input: &S::Repr) -> Vec<f64> {
         // Compute the feature vector phi:
         let phi = self.projector.project(input);
@@ -48,7 +64,8 @@ impl<S: Space, P: Projection<S>> Function<S::Repr, Vec<f64>> for DenseLinear<S,
     }
 }
 
-impl<S: Space, P: Projection<S>> Parameterised<S::Repr, f64> for DenseLinear<S, P> {
+impl<S: Space, P: Projection<S>, O: Optimiser> Parameterised<S::Repr, f64>
+    for DenseLinear<S, P, O> {
     fn update(&mut self, input: &S::Repr, error: f64) {
         // Compute the feature vector phi:
         let phi = self.projector.project(input);
@@ -57,7 +74,8 @@ impl<S: Space, P: Projection<S>> Parameterised<S::Repr, f64> for DenseLinear<S,
     }
 }
 
-impl<S: Space, P: Projection<S>> Parameterised<S::Repr, Vec<f64>> for DenseLinear<S, P> {
+impl<S: Space, P: Projection<S>, O: Optimiser> Parameterised<S::Repr, Vec<f64>>
+    for DenseLinear<S, P, O> {
     fn update(&mut self, input: &S::Repr, errors: Vec<f64>) {
         // Compute the feature vector phi:
         let phi = self.projector.project(input);
@@ -66,18 +84,18 @@ impl<S: Space, P: Projection<S>> Parameterised<S::Repr, Vec<f64>> for DenseLinea
     }
 }
 
-impl<S: Space, P: Projection<S>> VFunction<S> for DenseLinear<S, P> {
+impl<S: Space, P: Projection<S>, O: Optimiser> VFunction<S> for DenseLinear<S, P, O> {
     fn evaluate_phi(&self, phi: &Array1<f64>) -> f64 {
         dot(self.weights.column(0).as_slice().unwrap(),
             phi.as_slice().unwrap())
     }
 
     fn update_phi(&mut self, phi: &Array1<f64>, error: f64) {
-        self.weights.column_mut(0).scaled_add(error, phi);
+        self.step_column(phi, 0, error);
     }
 }
 
-impl<S: Space, P: Projection<S>> QFunction<S> for DenseLinear<S, P> {
+impl<S: Space, P: Projection<S>, O: Optimiser> QFunction<S> for DenseLinear<S, P, O> {
     fn evaluate_action(&self, input: &S::Repr, action: usize) -> f64 {
         let phi = self.projector.project(input);
 
@@ -99,19 +117,50 @@ impl<S: Space, P: Projection<S>> QFunction<S> for DenseLinear<S, P> {
     }
 
     fn update_phi(&mut self, phi: &Array1<f64>, errors: Vec<f64>) {
-        let phi_view = phi.view().into_shape((self.weights.rows(), 1)).unwrap();
-        let error_matrix = ArrayView::from_shape((1, self.weights.cols()), errors.as_slice())
-            .unwrap();
+        let (n_features, n_outputs) = (phi.len(), errors.len());
+
+        // Outer product of the features and errors:
+        let gradient = phi.view().into_shape((n_features, 1)).unwrap()
+            .dot(&Array1::from_vec(errors).into_shape((1, n_outputs)).unwrap());
 
-        self.weights += &phi_view.dot(&error_matrix);
+        self.optimiser.step(&mut self.weights, &gradient);
     }
 
     fn update_action_phi(&mut self, phi: &Array1<f64>, action: usize, error: f64) {
-        self.weights.column_mut(action).scaled_add(error, &phi);
+        self.step_column(phi, action, error);
+    }
+}
+
+impl<S: Space, P: Projection<S>, O: Optimiser> LinearQFunction<S> for DenseLinear<S, P, O> {
+    fn n_features(&self) -> usize {
+        self.weights.len()
+    }
+
+    fn phi_action(&self, input: &S::Repr, action: usize) -> Array1<f64> {
+        let n_cols = self.weights.cols();
+        let mut phi = Array1::zeros((self.weights.len(),));
+
+        for (i, v) in self.projector.project(input).iter().enumerate() {
+            phi[i * n_cols + action] = *v;
+        }
+
+        phi
+    }
+
+    fn evaluate_features(&self, phi: &Array1<f64>) -> f64 {
+        self.weights.iter().zip(phi.iter()).fold(0.0, |acc, (w, p)| acc + w * p)
+    }
+
+    fn update_features(&mut self, phi: &Array1<f64>, error: f64) {
+        for (k, (w, p)) in self.weights.iter_mut().zip(phi.iter()).enumerate() {
+            if *p != 0.0 {
+                self.optimiser.step_entry(k, w, error * p);
+            }
+        }
     }
 }
 
-impl<S: Space, P: Projection<S>> Projection<S> for DenseLinear<S, P> {
+impl<S: Space, P: Projection<S>, O: Optimiser> Projection<S> for DenseLinear<S, P, O> {
     fn project(&self, input: &S::Repr) -> Array1<f64> {
         self.projector.project(input)
     }
@@ -135,34 +184,55 @@ impl<S: Space, P: Projection<S>> Projection<S> for DenseLinear<S, P> {
 
 
 #[derive(Serialize, Deserialize)]
-pub struct SparseLinear<S: Space, P: SparseProjection<S>> {
+pub struct SparseLinear<S: Space, P: SparseProjection<S>, O: Optimiser = SGD> {
     pub projector: P,
     pub weights: Array2<f64>,
+    pub optimiser: O,
 
     phantom: PhantomData<S>,
 }
 
 impl<S: Space, P: SparseProjection<S>> SparseLinear<S, P> {
     pub fn new(projector: P, n_outputs: usize) -> Self {
+        SparseLinear::with_optimiser(projector, n_outputs, SGD::default())
+    }
+}
+
+impl<S: Space, P: SparseProjection<S>, O: Optimiser> SparseLinear<S, P, O> {
+    pub fn with_optimiser(projector: P, n_outputs: usize, optimiser: O) -> Self {
         let n_features = projector.size();
 
-        Self {
+        SparseLinear {
             projector: projector,
             weights: Array2::<f64>::zeros((n_features, n_outputs)),
+            optimiser: optimiser,
 
             phantom: PhantomData,
         }
     }
+
+    fn step_indices(&mut self, indices: &Array1<usize>, column: usize, error: f64) {
+        let n_cols = self.weights.cols();
+        let scaled_error = error / self.projector.sparsity() as f64;
+
+        for i in indices.iter() {
+            self.optimiser.step_entry(*i * n_cols + column,
+                                      &mut self.weights[(*i, column)],
+                                      scaled_error);
+        }
+    }
 }
 
-impl<S: Space, P: SparseProjection<S>> Function<S::Repr, f64> for SparseLinear<S, P> {
+impl<S: Space, P: SparseProjection<S>, O: Optimiser> Function<S::Repr, f64>
+    for SparseLinear<S, P, O> {
     fn evaluate(&self, input: &S::Repr) -> f64 {
         self.projector.project_sparse(input).fold(0.0f64,
                                                   |acc, i| acc + self.weights.column(0)[*i])
     }
 }
 
-impl<S: Space, P: SparseProjection<S>> Function<S::Repr, Vec<f64>> for SparseLinear<S, P> {
+impl<S: Space, P: SparseProjection<S>, O: Optimiser> Function<S::Repr, Vec<f64>>
+    for SparseLinear<S, P, O> {
     fn evaluate(&self, input: &S::Repr) -> Vec<f64> {
         let indices = self.projector.project_sparse(input);
 
@@ -172,41 +242,343 @@ impl<S: Space, P: SparseProjection<S>> Function<S::Repr, Vec<f64>> for SparseLin
     }
 }
 
-impl<S: Space, P: SparseProjection<S>> Parameterised<S::Repr, f64> for SparseLinear<S, P> {
+impl<S: Space, P: SparseProjection<S>, O: Optimiser> Parameterised<S::Repr, f64>
+    for SparseLinear<S, P, O> {
     fn update(&mut self, input: &S::Repr, error: f64) {
-        let scaled_error = error / self.projector.sparsity() as f64;
+        let indices = self.projector.project_sparse(input);
 
-        for i in self.projector.project_sparse(input).iter() {
-            self.weights.column_mut(0)[*i] += scaled_error;
-        }
+        self.step_indices(&indices, 0, error);
     }
 }
 
-impl<S: Space, P: SparseProjection<S>> Parameterised<S::Repr, Vec<f64>> for SparseLinear<S, P> {
+impl<S: Space, P: SparseProjection<S>, O: Optimiser> Parameterised<S::Repr, Vec<f64>>
+    for SparseLinear<S, P, O> {
     fn update(&mut self, input: &S::Repr, errors: Vec<f64>) {
-        for c in 0..self.weights.cols() {
-            let scaled_error = errors[c] / self.projector.sparsity() as f64;
+        let indices = self.projector.project_sparse(input);
 
-            for i in self.projector.project_sparse(input).iter() {
-                self.weights.column_mut(c)[*i] += scaled_error;
-            }
+        for (c, e) in errors.into_iter().enumerate() {
+            self.step_indices(&indices, c, e);
         }
     }
 }
 
-impl<S: Space, P: SparseProjection<S>> VFunction<S> for SparseLinear<S, P> {}
+impl<S: Space, P: SparseProjection<S>, O: Optimiser> VFunction<S> for SparseLinear<S, P, O> {}
 
-impl<S: Space, P: SparseProjection<S>> QFunction<S> for SparseLinear<S, P> {
+impl<S: Space, P: SparseProjection<S>, O: Optimiser> QFunction<S> for SparseLinear<S, P, O> {
     fn evaluate_action(&self, input: &S::Repr, action: usize) -> f64 {
         self.projector.project_sparse(input).fold(0.0f64,
                                                   |acc, i| acc + self.weights.column(action)[*i])
     }
 
     fn update_action(&mut self, input: &S::Repr, action: usize, error: f64) {
+        let indices = self.projector.project_sparse(input);
+
+        self.step_indices(&indices, action, error);
+    }
+}
+
+
+/// Sparse linear function approximator with lazily allocated weights.
+///
+/// Unlike `SparseLinear`, which stores a dense `size() x n_outputs` matrix,
+/// the weights of each feature are only allocated the first time that
+/// feature is updated. Features that have never been visited evaluate to
+/// zero. This makes very large (e.g. hashed tile coding) feature spaces
+/// practical, since memory grows with the number of features visited rather
+/// than with the size of the projection.
+#[derive(Serialize, Deserialize)]
+pub struct HashedLinear<S: Space, P: SparseProjection<S>, O: Optimiser = SGD> {
+    pub projector: P,
+    pub weights: HashMap<usize, Vec<f64>>,
+    pub optimiser: O,
+
+    n_outputs: usize,
+
+    phantom: PhantomData<S>,
+}
+
+impl<S: Space, P: SparseProjection<S>> HashedLinear<S, P> {
+    pub fn new(projector: P, n_outputs: usize) -> Self {
+        HashedLinear::with_optimiser(projector, n_outputs, SGD::default())
+    }
+}
+
+impl<S: Space, P: SparseProjection<S>, O: Optimiser> HashedLinear<S, P, O> {
+    pub fn with_optimiser(projector: P, n_outputs: usize, optimiser: O) -> Self {
+        HashedLinear {
+            projector: projector,
+            weights: HashMap::new(),
+            optimiser: optimiser,
+
+            n_outputs: n_outputs,
+
+            phantom: PhantomData,
+        }
+    }
+
+    /// Returns the number of features with allocated weights.
+    pub fn n_allocated(&self) -> usize {
+        self.weights.len()
+    }
+
+    fn evaluate_indices(&self, indices: &Array1<usize>, column: usize) -> f64 {
+        indices.iter()
+            .filter_map(|i| self.weights.get(i))
+            .fold(0.0, |acc, w| acc + w[column])
+    }
+
+    fn evaluate_indices_all(&self, indices: &Array1<usize>) -> Vec<f64> {
+        let mut out = vec![0.0; self.n_outputs];
+
+        for w in indices.iter().filter_map(|i| self.weights.get(i)) {
+            for (o, x) in out.iter_mut().zip(w.iter()) {
+                *o += *x;
+            }
+        }
+
+        out
+    }
+
+    fn step_indices(&mut self, indices: &Array1<usize>, column: usize, error: f64) {
+        let n_outputs = self.n_outputs;
         let scaled_error = error / self.projector.sparsity() as f64;
 
-        for i in self.projector.project_sparse(input).iter() {
-            self.weights.column_mut(action)[*i] += scaled_error;
+        for i in indices.iter() {
+            let w = self.weights.entry(*i).or_insert_with(|| vec![0.0; n_outputs]);
+
+            self.optimiser.step_entry(*i * n_outputs + column, &mut w[column], scaled_error);
+        }
+    }
+}
+
+impl<S: Space, P: SparseProjection<S>, O: Optimiser> Function<S::Repr, f64>
+    for HashedLinear<S, P, O> {
+    fn evaluate(&self, input: &S::Repr) -> f64 {
+        let indices = self.projector.project_sparse(input);
+
+        self.evaluate_indices(&indices, 0)
+    }
+}
+
+impl<S: Space, P: SparseProjection<S>, O: Optimiser> Function<S::Repr, Vec<f64>>
+    for HashedLinear<S, P, O> {
+    fn evaluate(&self, input: &S::Repr) -> Vec<f64> {
+        let indices = self.projector.project_sparse(input);
+
+        self.evaluate_indices_all(&indices)
+    }
+}
+
+impl<S: Space, P: SparseProjection<S>, O: Optimiser> Parameterised<S::Repr, f64>
+    for HashedLinear<S, P, O> {
+    fn update(&mut self, input: &S::Repr, error: f64) {
+        let indices = self.projector.project_sparse(input);
+
+        self.step_indices(&indices, 0, error);
+    }
+}
+
+impl<S: Space, P: SparseProjection<S>, O: Optimiser> Parameterised<S::Repr, Vec<f64>>
+    for HashedLinear<S, P, O> {
+    fn update(&mut self, input: &S::Repr, errors: Vec<f64>) {
+        let indices = self.projector.project_sparse(input);
+
+        for (c, e) in errors.into_iter().enumerate() {
+            self.step_indices(&indices, c, e);
+        }
+    }
+}
+
+impl<S: Space, P: SparseProjection<S>, O: Optimiser> VFunction<S> for HashedLinear<S, P, O> {}
+
+impl<S: Space, P: SparseProjection<S>, O: Optimiser> QFunction<S> for HashedLinear<S, P, O> {
+    fn evaluate_action(&self, input: &S::Repr, action: usize) -> f64 {
+        let indices = self.projector.project_sparse(input);
+
+        self.evaluate_indices(&indices, action)
+    }
+
+    fn update_action(&mut self, input: &S::Repr, action: usize, error: f64) {
+        let indices = self.projector.project_sparse(input);
+
+        self.step_indices(&indices, action, error);
+    }
+}
+
+
+/// Linear action-value function over state-action features, Q(s, a) = w·φ(s, a).
+#[derive(Serialize, Deserialize)]
+pub struct ActionLinear<S: Space, P: ActionProjection<S>, O: Optimiser = SGD> {
+    pub projector: P,
+    pub weights: Array1<f64>,
+    pub optimiser: O,
+
+    phantom: PhantomData<S>,
+}
+
+impl<S: Space, P: ActionProjection<S>> ActionLinear<S, P> {
+    pub fn new(projector: P) -> Self {
+        ActionLinear::with_optimiser(projector, SGD::default())
+    }
+}
+
+impl<S: Space, P: ActionProjection<S>, O: Optimiser> ActionLinear<S, P, O> {
+    pub fn with_optimiser(projector: P, optimiser: O) -> Self {
+        let n_features = projector.size();
+
+        ActionLinear {
+            projector: projector,
+            weights: Array1::zeros((n_features,)),
+            optimiser: optimiser,
+
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<S: Space, P: ActionProjection<S>, O: Optimiser> Function<S::Repr, Vec<f64>>
+    for ActionLinear<S, P, O> {
+    fn evaluate(&self, input: &S::Repr) -> Vec<f64> {
+        (0..self.projector.n_actions()).map(|a| self.evaluate_action(input, a)).collect()
+    }
+}
+
+impl<S: Space, P: ActionProjection<S>, O: Optimiser> Parameterised<S::Repr, Vec<f64>>
+    for ActionLinear<S, P, O> {
+    fn update(&mut self, input: &S::Repr, errors: Vec<f64>) {
+        for (a, e) in errors.into_iter().enumerate() {
+            self.update_action(input, a, e);
+        }
+    }
+}
+
+impl<S: Space, P: ActionProjection<S>, O: Optimiser> QFunction<S> for ActionLinear<S, P, O> {
+    fn evaluate_action(&self, input: &S::Repr, action: usize) -> f64 {
+        let phi = self.projector.project_action(input, action);
+
+        self.evaluate_features(&phi)
+    }
+
+    fn update_action(&mut self, input: &S::Repr, action: usize, error: f64) {
+        let phi = self.projector.project_action(input, action);
+
+        self.update_features(&phi, error);
+    }
+}
+
+impl<S: Space, P: ActionProjection<S>, O: Optimiser> LinearQFunction<S>
+    for ActionLinear<S, P, O> {
+    fn n_features(&self) -> usize {
+        self.weights.len()
+    }
+
+    fn phi_action(&self, input: &S::Repr, action: usize) -> Array1<f64> {
+        self.projector.project_action(input, action)
+    }
+
+    fn evaluate_features(&self, phi: &Array1<f64>) -> f64 {
+        self.weights.dot(phi)
+    }
+
+    fn update_features(&mut self, phi: &Array1<f64>, error: f64) {
+        for (k, (w, p)) in self.weights.iter_mut().zip(phi.iter()).enumerate() {
+            if *p != 0.0 {
+                self.optimiser.step_entry(k, w, error * p);
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{Linear, SparseLinear, HashedLinear, ActionLinear};
+    use fa::{Function, Parameterised, QFunction, LinearQFunction};
+    use fa::projection::{UniformGrid, PerAction};
+    use geometry::RegularSpace;
+    use geometry::dimensions::Partitioned;
+
+    fn grid() -> UniformGrid {
+        UniformGrid::new(RegularSpace::new().push(Partitioned::new(0.0, 10.0, 10)))
+    }
+
+    fn values<F: Function<Vec<f64>, Vec<f64>>>(f: &F, input: &Vec<f64>) -> Vec<f64> {
+        f.evaluate(input)
+    }
+
+    #[test]
+    fn test_dense_update() {
+        let mut f = Linear::new(grid(), 2);
+
+        f.update(&vec![2.5], vec![1.0, -2.0]);
+        QFunction::update_action(&mut f, &vec![2.5], 1, 0.5);
+
+        assert_eq!(values(&f, &vec![2.5]), vec![1.0, -1.5]);
+        assert_eq!(values(&f, &vec![7.5]), vec![0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_hashed_allocates_lazily() {
+        let mut f = HashedLinear::new(grid(), 2);
+
+        assert_eq!(values(&f, &vec![2.5]), vec![0.0, 0.0]);
+        assert_eq!(f.n_allocated(), 0);
+
+        f.update(&vec![2.5], vec![1.0, -2.0]);
+
+        assert_eq!(f.n_allocated(), 1);
+        assert_eq!(values(&f, &vec![2.5]), vec![1.0, -2.0]);
+        assert_eq!(values(&f, &vec![7.5]), vec![0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_hashed_matches_sparse() {
+        let mut dense = SparseLinear::new(grid(), 3);
+        let mut hashed = HashedLinear::new(grid(), 3);
+
+        for i in 0..20 {
+            let x = vec![(i % 10) as f64 + 0.5];
+
+            dense.update(&x, vec![0.1 * i as f64, -0.5, 1.0]);
+            hashed.update(&x, vec![0.1 * i as f64, -0.5, 1.0]);
+
+            QFunction::update_action(&mut dense, &x, 1, 0.25);
+            QFunction::update_action(&mut hashed, &x, 1, 0.25);
+        }
+
+        for i in 0..10 {
+            let x = vec![i as f64 + 0.5];
+
+            assert_eq!(values(&dense, &x), values(&hashed, &x));
+            assert_eq!(dense.evaluate_action(&x, 1), hashed.evaluate_action(&x, 1));
+        }
+    }
+
+    #[test]
+    fn test_action_linear_matches_dense() {
+        let mut dense = Linear::new(grid(), 2);
+        let mut per_action = ActionLinear::new(PerAction::new(grid(), 2));
+
+        for i in 0..20 {
+            let x = vec![(i % 10) as f64 + 0.5];
+
+            QFunction::update_action(&mut dense, &x, i % 2, 0.1 * i as f64);
+            QFunction::update_action(&mut per_action, &x, i % 2, 0.1 * i as f64);
+        }
+
+        assert_eq!(dense.n_features(), per_action.n_features());
+
+        for i in 0..10 {
+            let x = vec![i as f64 + 0.5];
+
+            assert_eq!(values(&dense, &x), values(&per_action, &x));
+
+            for a in 0..2 {
+                let phi = dense.phi_action(&x, a);
+
+                assert_eq!(phi, per_action.phi_action(&x, a));
+                assert_eq!(dense.evaluate_features(&phi), per_action.evaluate_features(&phi));
+            }
         }
     }
 }
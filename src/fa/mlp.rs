@@ -0,0 +1,319 @@
+use super::{Function, Parameterised, VFunction, QFunction};
+use geometry::RegularSpace;
+use geometry::dimensions::Continuous;
+use ndarray::{Array1, Array2};
+use rand::thread_rng;
+use rand::distributions::{Range, IndependentSample};
+
+
+/// Elementwise nonlinearity applied at the output of a layer.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Activation {
+    Identity,
+    Sigmoid,
+    Tanh,
+    ReLU,
+    LeakyReLU(f64),
+    Softplus,
+}
+
+impl Activation {
+    pub fn apply(&self, x: f64) -> f64 {
+        match *self {
+            Activation::Identity => x,
+            Activation::Sigmoid => 1.0 / (1.0 + (-x).exp()),
+            Activation::Tanh => x.tanh(),
+            Activation::ReLU => x.max(0.0),
+            Activation::LeakyReLU(a) => if x > 0.0 { x } else { a * x },
+            Activation::Softplus => x.max(0.0) + (-x.abs()).exp().ln_1p(),
+        }
+    }
+
+    /// Returns the derivative evaluated at the pre-activation `x`.
+    pub fn derivative(&self, x: f64) -> f64 {
+        match *self {
+            Activation::Identity => 1.0,
+            Activation::Sigmoid => {
+                let s = Activation::Sigmoid.apply(x);
+
+                s * (1.0 - s)
+            },
+            Activation::Tanh => 1.0 - x.tanh().powi(2),
+            Activation::ReLU => if x > 0.0 { 1.0 } else { 0.0 },
+            Activation::LeakyReLU(a) => if x > 0.0 { 1.0 } else { a },
+            Activation::Softplus => Activation::Sigmoid.apply(x),
+        }
+    }
+}
+
+
+/// Fully connected layer computing f(W'x + b).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Layer {
+    pub weights: Array2<f64>,
+    pub bias: Array1<f64>,
+    pub activation: Activation,
+}
+
+impl Layer {
+    /// Constructs a layer with Xavier/Glorot uniform initialised weights.
+    pub fn new(n_inputs: usize, n_outputs: usize, activation: Activation) -> Self {
+        let limit = (6.0 / (n_inputs + n_outputs) as f64).sqrt();
+
+        let mut rng = thread_rng();
+        let range = Range::new(-limit, limit);
+
+        Layer {
+            weights: Array2::from_shape_fn((n_inputs, n_outputs), |_| range.ind_sample(&mut rng)),
+            bias: Array1::zeros((n_outputs,)),
+            activation: activation,
+        }
+    }
+
+    fn preactivation(&self, input: &Array1<f64>) -> Array1<f64> {
+        self.weights.t().dot(input) + &self.bias
+    }
+}
+
+
+/// Multilayer perceptron trained by backpropagation.
+///
+/// Layers are appended with `push`, the last of which defines the output of
+/// the network; e.g. `MLP::new(4).push(32, Activation::ReLU).push(2,
+/// Activation::Identity)`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MLP {
+    n_inputs: usize,
+    pub layers: Vec<Layer>,
+}
+
+impl MLP {
+    pub fn new(n_inputs: usize) -> Self {
+        MLP {
+            n_inputs: n_inputs,
+            layers: vec![],
+        }
+    }
+
+    pub fn push(mut self, n_units: usize, activation: Activation) -> Self {
+        let n_in = self.n_outputs();
+
+        self.layers.push(Layer::new(n_in, n_units, activation));
+        self
+    }
+
+    pub fn n_inputs(&self) -> usize {
+        self.n_inputs
+    }
+
+    pub fn n_outputs(&self) -> usize {
+        self.layers.last().map_or(self.n_inputs, |l| l.bias.len())
+    }
+
+    pub fn predict(&self, input: &[f64]) -> Array1<f64> {
+        self.layers.iter().fold(Array1::from_vec(input.to_vec()), |a, l| {
+            l.preactivation(&a).mapv(|z| l.activation.apply(z))
+        })
+    }
+
+    /// Returns the gradient of `errors . output` with respect to the weights
+    /// and biases of each layer.
+    pub fn gradients(&self,
+                     input: &[f64],
+                     errors: &Array1<f64>)
+                     -> Vec<(Array2<f64>, Array1<f64>)> {
+        // Forward pass, retaining the inputs and pre-activations of each layer:
+        let mut inputs = vec![Array1::from_vec(input.to_vec())];
+        let mut zs = vec![];
+
+        for l in self.layers.iter() {
+            let z = l.preactivation(inputs.last().unwrap());
+
+            inputs.push(z.mapv(|v| l.activation.apply(v)));
+            zs.push(z);
+        }
+
+        // Backward pass:
+        let mut grads = Vec::with_capacity(self.layers.len());
+        let mut delta = errors.clone();
+
+        for (i, l) in self.layers.iter().enumerate().rev() {
+            delta = delta * &zs[i].mapv(|v| l.activation.derivative(v));
+
+            let (n_in, n_out) = (l.weights.rows(), l.weights.cols());
+            let a = inputs[i].view().into_shape((n_in, 1)).unwrap();
+            let d = delta.view().into_shape((1, n_out)).unwrap();
+
+            grads.push((a.dot(&d), delta.clone()));
+            delta = l.weights.dot(&delta);
+        }
+
+        grads.reverse();
+        grads
+    }
+
+    /// Applies a single gradient step, with step sizes already folded into
+    /// `errors`.
+    pub fn backprop(&mut self, input: &[f64], errors: &Array1<f64>) {
+        let grads = self.gradients(input, errors);
+
+        for (l, (dw, db)) in self.layers.iter_mut().zip(grads.into_iter()) {
+            l.weights += &dw;
+            l.bias += &db;
+        }
+    }
+
+    /// Moves each parameter a fraction `tau` of the way towards the
+    /// corresponding parameter of `other`, which must share the same topology.
+    pub fn soft_update(&mut self, other: &MLP, tau: f64) {
+        for (l, o) in self.layers.iter_mut().zip(other.layers.iter()) {
+            l.weights *= 1.0 - tau;
+            l.weights.scaled_add(tau, &o.weights);
+
+            l.bias *= 1.0 - tau;
+            l.bias.scaled_add(tau, &o.bias);
+        }
+    }
+}
+
+impl Function<Vec<f64>, f64> for MLP {
+    fn evaluate(&self, input: &Vec<f64>) -> f64 {
+        self.predict(input)[0]
+    }
+}
+
+impl Function<Vec<f64>, Vec<f64>> for MLP {
+    fn evaluate(&self, input: &Vec<f64>) -> Vec<f64> {
+        self.predict(input).into_raw_vec()
+    }
+}
+
+impl Parameterised<Vec<f64>, f64> for MLP {
+    fn update(&mut self, input: &Vec<f64>, error: f64) {
+        let mut errors = Array1::zeros((self.n_outputs(),));
+        errors[0] = error;
+
+        self.backprop(input, &errors);
+    }
+}
+
+impl Parameterised<Vec<f64>, Vec<f64>> for MLP {
+    fn update(&mut self, input: &Vec<f64>, errors: Vec<f64>) {
+        self.backprop(input, &Array1::from_vec(errors));
+    }
+}
+
+impl VFunction<RegularSpace<Continuous>> for MLP {}
+
+impl QFunction<RegularSpace<Continuous>> for MLP {
+    fn evaluate_action(&self, input: &Vec<f64>, action: usize) -> f64 {
+        self.predict(input)[action]
+    }
+
+    fn update_action(&mut self, input: &Vec<f64>, action: usize, error: f64) {
+        let mut errors = Array1::zeros((self.n_outputs(),));
+        errors[action] = error;
+
+        self.backprop(input, &errors);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+
+    fn value(net: &MLP, x: &[f64]) -> f64 {
+        net.evaluate(&x.to_vec())
+    }
+
+    #[test]
+    fn test_dimensions() {
+        let net = MLP::new(3).push(8, Activation::Tanh).push(2, Activation::Identity);
+
+        assert_eq!(net.n_inputs(), 3);
+        assert_eq!(net.n_outputs(), 2);
+        assert_eq!(net.layers[0].weights.shape(), &[3, 8]);
+        assert_eq!(net.layers[1].weights.shape(), &[8, 2]);
+        assert_eq!(net.predict(&[0.0, 1.0, 2.0]).len(), 2);
+    }
+
+    #[test]
+    fn test_activations() {
+        for act in [Activation::Sigmoid, Activation::Tanh, Activation::LeakyReLU(0.1),
+                    Activation::Softplus].iter() {
+            for &x in [-2.0, -0.5, 0.3, 1.7].iter() {
+                let fd = (act.apply(x + 1e-6) - act.apply(x - 1e-6)) / 2e-6;
+
+                assert!((fd - act.derivative(x)).abs() < 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn test_gradients() {
+        let net = MLP::new(2).push(5, Activation::Tanh).push(1, Activation::Identity);
+        let x = [0.3, -0.7];
+
+        let grads = net.gradients(&x, &Array1::from_elem((1,), 1.0));
+
+        for (k, &(ref dw, _)) in grads.iter().enumerate() {
+            for i in 0..dw.rows() {
+                for j in 0..dw.cols() {
+                    let mut hi = net.clone();
+                    let mut lo = net.clone();
+
+                    hi.layers[k].weights[(i, j)] += 1e-6;
+                    lo.layers[k].weights[(i, j)] -= 1e-6;
+
+                    let fd = (value(&hi, &x) - value(&lo, &x)) / 2e-6;
+
+                    assert!((fd - dw[(i, j)]).abs() < 1e-5);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_xor() {
+        let mut net = MLP::new(2).push(8, Activation::Tanh).push(1, Activation::Identity);
+        let data = [([0.0, 0.0], 0.0), ([0.0, 1.0], 1.0), ([1.0, 0.0], 1.0), ([1.0, 1.0], 0.0)];
+
+        for _ in 0..5000 {
+            for &(x, y) in data.iter() {
+                let err = y - value(&net, &x);
+
+                net.update(&x.to_vec(), 0.05 * err);
+            }
+        }
+
+        for &(x, y) in data.iter() {
+            assert!((value(&net, &x) - y).abs() < 0.1);
+        }
+    }
+
+    #[test]
+    fn test_serialisation() {
+        let net = MLP::new(2).push(4, Activation::ReLU).push(3, Activation::Identity);
+        let json = serde_json::to_string(&net).unwrap();
+        let net2: MLP = serde_json::from_str(&json).unwrap();
+
+        assert!(net.predict(&[0.5, -0.5]).all_close(&net2.predict(&[0.5, -0.5]), 1e-12));
+    }
+
+    #[test]
+    fn test_soft_update() {
+        let mut net = MLP::new(2).push(4, Activation::ReLU).push(1, Activation::Identity);
+        let target = MLP::new(2).push(4, Activation::ReLU).push(1, Activation::Identity);
+
+        let w0 = net.layers[0].weights.clone();
+        net.soft_update(&target, 0.25);
+
+        let expected = &w0 * 0.75 + &(&target.layers[0].weights * 0.25);
+        assert!(net.layers[0].weights.all_close(&expected, 1e-12));
+
+        net.soft_update(&target, 1.0);
+        assert!(net.layers[1].weights.all_close(&target.layers[1].weights, 1e-12));
+    }
+}
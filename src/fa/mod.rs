@@ -76,33 +76,6 @@ pub trait QFunction<S: Space>
     fn update_action_phi(&mut self, _: &Array1<f64>, _: usize, _: f64) {
         unimplemented!();
     }
-
-    // NOTE: Eventually we may want to distinguish between the feature vector
-    //       for each action. I can't see this being needed anytime soon so
-    //       for now we will just have a single entry point for computing phi.
-    // fn phi(&self, input: &S::Repr) -> Array2<f64> {
-    // unimplemented!()
-    // }
-
-    // fn phi_action(&self, input: &S::Repr, _: usize) -> Array1<f64>{
-    // unimplemented!()
-    // }
-
-    // fn evaluate_phi(&self, phi: &Array2<f64>) -> f64 {
-    // unimplemented!();
-    // }
-
-    // fn evaluate_action_phi(&self, phi: &Array1<f64>, action: usize) -> f64 {
-    // unimplemented!();
-    // }
-
-    // fn update_phi(&mut self, phi: &Array2<f64>, error: f64) -> f64 {
-    // unimplemented!();
-    // }
-
-    // fn update_action_phi(&mut self, phi: &Array1<f64>, action: usize, error: f64) -> f64 {
-    // unimplemented!();
-    // }
 }
 
 impl<S: Space, T> QFunction<S> for Box<T>
@@ -134,11 +107,60 @@ impl<S: Space, T> QFunction<S> for Box<T>
 }
 
 
+/// An interface for action-value functions that are linear in a set of
+/// state-action features, i.e. Q(s, a) = w·φ(s, a).
+pub trait LinearQFunction<S: Space>: QFunction<S> {
+    /// Returns the number of state-action features (i.e. the length of φ).
+    fn n_features(&self) -> usize;
+
+    /// Returns the state-action feature vector φ(s, a).
+    fn phi_action(&self, input: &S::Repr, action: usize) -> Array1<f64>;
+
+    fn evaluate_features(&self, phi: &Array1<f64>) -> f64;
+    fn update_features(&mut self, phi: &Array1<f64>, error: f64);
+}
+
+impl<S: Space, T> LinearQFunction<S> for Box<T>
+    where T: LinearQFunction<S>
+{
+    fn n_features(&self) -> usize {
+        (**self).n_features()
+    }
+
+    fn phi_action(&self, input: &S::Repr, action: usize) -> Array1<f64> {
+        (**self).phi_action(input, action)
+    }
+
+    fn evaluate_features(&self, phi: &Array1<f64>) -> f64 {
+        (**self).evaluate_features(phi)
+    }
+
+    fn update_features(&mut self, phi: &Array1<f64>, error: f64) {
+        (**self).update_features(phi, error)
+    }
+}
+
+
 mod table;
-pub use self::table::Table;
+pub use self::table::{Table, DenseTable, Accumulate};
 
 pub mod projection;
-pub use self::projection::{Projection, SparseProjection};
+pub use self::projection::{Projection, SparseProjection, ActionProjection};
+
+pub mod optim;
+pub use self::optim::Optimiser;
 
 mod linear;
-pub use self::linear::{Linear, SparseLinear};
+pub use self::linear::{Linear, SparseLinear, HashedLinear, ActionLinear};
+
+mod adaptive_rbf;
+pub use self::adaptive_rbf::{AdaptiveRBFNetwork, Growth};
+
+mod regression_tree;
+pub use self::regression_tree::RegressionTree;
+
+mod mlp;
+pub use self::mlp::{MLP, Layer, Activation};
+
+mod categorical;
+pub use self::categorical::{CategoricalLinear, project_distribution};
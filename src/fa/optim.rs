@@ -0,0 +1,338 @@
+//! Optimisers used to apply (ascent) updates to the weights of a function
+//! approximator.
+//!
+//! Each optimiser maps the raw increment for a weight, i.e. the product of the
+//! (step size scaled) error and the relevant feature, to the increment that is
+//! actually applied. Per-weight state is created lazily, keyed by the flat
+//! (row-major) index of the weight, so sparse updates only pay for the
+//! weights they touch.
+use ndarray::{Array1, Array2};
+use std::collections::HashMap;
+
+
+pub trait Optimiser {
+    /// Returns the increment to apply to the weight at `index` given the raw
+    /// ascent direction `gradient`.
+    fn delta(&mut self, index: usize, gradient: f64) -> f64;
+
+    fn step_entry(&mut self, index: usize, weight: &mut f64, gradient: f64) {
+        *weight += self.delta(index, gradient);
+    }
+
+    fn step(&mut self, weights: &mut Array2<f64>, gradient: &Array2<f64>) {
+        for (i, (w, g)) in weights.iter_mut().zip(gradient.iter()).enumerate() {
+            self.step_entry(i, w, *g);
+        }
+    }
+
+    /// Steps a single `column` of `weights` in the direction `error * phi`.
+    fn step_column(&mut self,
+                   weights: &mut Array2<f64>,
+                   column: usize,
+                   phi: &Array1<f64>,
+                   error: f64) {
+        let n_cols = weights.cols();
+
+        for (i, p) in phi.iter().enumerate() {
+            self.step_entry(i * n_cols + column, &mut weights[(i, column)], error * p);
+        }
+    }
+}
+
+
+/// Plain stochastic gradient ascent.
+///
+/// With the default learning rate of 1.0 the raw increments are applied
+/// unchanged, which is the behaviour expected by agents that fold their own
+/// step size into the error.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct SGD {
+    pub lr: f64,
+}
+
+impl SGD {
+    pub fn new(lr: f64) -> Self {
+        SGD { lr: lr }
+    }
+}
+
+impl Default for SGD {
+    fn default() -> Self {
+        SGD::new(1.0)
+    }
+}
+
+impl Optimiser for SGD {
+    fn delta(&mut self, _: usize, gradient: f64) -> f64 {
+        self.lr * gradient
+    }
+
+    // SGD is stateless, so whole arrays can be updated in one vectorised pass:
+    fn step(&mut self, weights: &mut Array2<f64>, gradient: &Array2<f64>) {
+        weights.scaled_add(self.lr, gradient);
+    }
+
+    fn step_column(&mut self,
+                   weights: &mut Array2<f64>,
+                   column: usize,
+                   phi: &Array1<f64>,
+                   error: f64) {
+        weights.column_mut(column).scaled_add(self.lr * error, phi);
+    }
+}
+
+
+/// Stochastic gradient ascent with classical momentum.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Momentum {
+    pub lr: f64,
+    pub momentum: f64,
+
+    velocity: HashMap<usize, f64>,
+}
+
+impl Momentum {
+    pub fn new(lr: f64, momentum: f64) -> Self {
+        Momentum {
+            lr: lr,
+            momentum: momentum,
+
+            velocity: HashMap::new(),
+        }
+    }
+}
+
+impl Optimiser for Momentum {
+    fn delta(&mut self, index: usize, gradient: f64) -> f64 {
+        let v = self.velocity.entry(index).or_insert(0.0);
+        *v = self.momentum * *v + self.lr * gradient;
+
+        *v
+    }
+}
+
+
+/// Stochastic gradient ascent with Nesterov's accelerated momentum.
+///
+/// Sutskever, Ilya, et al. "On the importance of initialization and momentum
+/// in deep learning." International conference on machine learning. 2013.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Nesterov {
+    pub lr: f64,
+    pub momentum: f64,
+
+    velocity: HashMap<usize, f64>,
+}
+
+impl Nesterov {
+    pub fn new(lr: f64, momentum: f64) -> Self {
+        Nesterov {
+            lr: lr,
+            momentum: momentum,
+
+            velocity: HashMap::new(),
+        }
+    }
+}
+
+impl Optimiser for Nesterov {
+    fn delta(&mut self, index: usize, gradient: f64) -> f64 {
+        let v = self.velocity.entry(index).or_insert(0.0);
+        *v = self.momentum * *v + self.lr * gradient;
+
+        self.momentum * *v + self.lr * gradient
+    }
+}
+
+
+/// Per-weight step sizes from a running average of squared gradients.
+///
+/// Tieleman, Tijmen, and Geoffrey Hinton. "Lecture 6.5-rmsprop: Divide the
+/// gradient by a running average of its recent magnitude." COURSERA: Neural
+/// networks for machine learning 4.2 (2012): 26-31.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RMSProp {
+    pub lr: f64,
+    pub decay: f64,
+    pub eps: f64,
+
+    cache: HashMap<usize, f64>,
+}
+
+impl RMSProp {
+    pub fn new(lr: f64, decay: f64) -> Self {
+        RMSProp {
+            lr: lr,
+            decay: decay,
+            eps: 1e-8,
+
+            cache: HashMap::new(),
+        }
+    }
+}
+
+impl Optimiser for RMSProp {
+    fn delta(&mut self, index: usize, gradient: f64) -> f64 {
+        let c = self.cache.entry(index).or_insert(0.0);
+        *c = self.decay * *c + (1.0 - self.decay) * gradient * gradient;
+
+        self.lr * gradient / (c.sqrt() + self.eps)
+    }
+}
+
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+struct Moments {
+    m: f64,
+    v: f64,
+    t: i32,
+}
+
+/// Adaptive moment estimation.
+///
+/// Kingma, Diederik P., and Jimmy Ba. "Adam: A method for stochastic
+/// optimization." arXiv preprint arXiv:1412.6980 (2014).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Adam {
+    pub lr: f64,
+    pub beta1: f64,
+    pub beta2: f64,
+    pub eps: f64,
+
+    moments: HashMap<usize, Moments>,
+}
+
+impl Adam {
+    pub fn new(lr: f64) -> Self {
+        Adam {
+            lr: lr,
+            beta1: 0.9,
+            beta2: 0.999,
+            eps: 1e-8,
+
+            moments: HashMap::new(),
+        }
+    }
+
+    pub fn with_betas(mut self, beta1: f64, beta2: f64) -> Self {
+        self.beta1 = beta1;
+        self.beta2 = beta2;
+        self
+    }
+}
+
+impl Optimiser for Adam {
+    fn delta(&mut self, index: usize, gradient: f64) -> f64 {
+        let (b1, b2) = (self.beta1, self.beta2);
+        let mo = self.moments.entry(index).or_insert_with(Moments::default);
+
+        mo.t += 1;
+        mo.m = b1 * mo.m + (1.0 - b1) * gradient;
+        mo.v = b2 * mo.v + (1.0 - b2) * gradient * gradient;
+
+        let m_hat = mo.m / (1.0 - b1.powi(mo.t));
+        let v_hat = mo.v / (1.0 - b2.powi(mo.t));
+
+        self.lr * m_hat / (v_hat.sqrt() + self.eps)
+    }
+}
+
+
+/// Per-weight step sizes from the accumulated sum of squared gradients.
+///
+/// Duchi, John, Elad Hazan, and Yoram Singer. "Adaptive subgradient methods
+/// for online learning and stochastic optimization." Journal of Machine
+/// Learning Research 12.Jul (2011): 2121-2159.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AdaGrad {
+    pub lr: f64,
+    pub eps: f64,
+
+    cache: HashMap<usize, f64>,
+}
+
+impl AdaGrad {
+    pub fn new(lr: f64) -> Self {
+        AdaGrad {
+            lr: lr,
+            eps: 1e-8,
+
+            cache: HashMap::new(),
+        }
+    }
+}
+
+impl Optimiser for AdaGrad {
+    fn delta(&mut self, index: usize, gradient: f64) -> f64 {
+        let c = self.cache.entry(index).or_insert(0.0);
+        *c += gradient * gradient;
+
+        self.lr * gradient / (c.sqrt() + self.eps)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::{arr1, arr2};
+
+    // Maximises -(w - 3)^2 from w = 0 and returns the final weight.
+    fn ascend<O: Optimiser>(opt: &mut O, n_steps: usize) -> f64 {
+        let mut w = 0.0;
+
+        for _ in 0..n_steps {
+            let g = -2.0 * (w - 3.0);
+
+            opt.step_entry(0, &mut w, g);
+        }
+
+        w
+    }
+
+    #[test]
+    fn test_sgd() {
+        let mut opt = SGD::default();
+        let mut w = arr2(&[[1.0, 2.0], [3.0, 4.0]]);
+
+        opt.step(&mut w, &arr2(&[[0.5, -0.5], [0.0, 1.0]]));
+
+        assert_eq!(w, arr2(&[[1.5, 1.5], [3.0, 5.0]]));
+        assert!((ascend(&mut SGD::new(0.1), 200) - 3.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_step_column() {
+        let mut w = arr2(&[[1.0, 2.0], [3.0, 4.0]]);
+
+        SGD::new(0.5).step_column(&mut w, 1, &arr1(&[1.0, -2.0]), 2.0);
+        assert_eq!(w, arr2(&[[1.0, 3.0], [3.0, 2.0]]));
+
+        // The default, per-entry implementation must agree with SGD's:
+        Momentum::new(0.5, 0.0).step_column(&mut w, 0, &arr1(&[1.0, -2.0]), 2.0);
+        assert_eq!(w, arr2(&[[2.0, 3.0], [1.0, 2.0]]));
+    }
+
+    #[test]
+    fn test_momentum() {
+        assert!((ascend(&mut Momentum::new(0.05, 0.9), 500) - 3.0).abs() < 1e-4);
+        assert!((ascend(&mut Nesterov::new(0.05, 0.9), 500) - 3.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_adaptive() {
+        assert!((ascend(&mut RMSProp::new(0.01, 0.9), 2000) - 3.0).abs() < 5e-2);
+        assert!((ascend(&mut Adam::new(0.05), 2000) - 3.0).abs() < 5e-2);
+        assert!((ascend(&mut AdaGrad::new(1.0), 2000) - 3.0).abs() < 5e-2);
+    }
+
+    #[test]
+    fn test_lazy_state() {
+        let mut opt = Adam::new(0.1);
+
+        // First step of Adam is ~lr * sign(g), independently for each weight:
+        assert!((opt.delta(0, 5.0) - 0.1).abs() < 1e-6);
+        assert!((opt.delta(7, -0.01) + 0.1).abs() < 1e-6);
+        assert_eq!(opt.moments.len(), 2);
+    }
+}
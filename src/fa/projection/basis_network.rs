@@ -3,17 +3,18 @@ use fa::Function;
 use geometry::RegularSpace;
 use geometry::dimensions::Continuous;
 use geometry::kernels::Kernel;
-use ndarray::Array1;
+use ndarray::{Array1, Array2};
 
 
 /// Represents the location and kernel associated with a basis function.
-pub struct BasisFunction {
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BasisFunction<K: Kernel> {
     pub loc: Vec<f64>,
-    pub kernel: Box<Kernel>,
+    pub kernel: K,
 }
 
-impl BasisFunction {
-    pub fn new(loc: Vec<f64>, kernel: Box<Kernel>) -> Self {
+impl<K: Kernel> BasisFunction<K> {
+    pub fn new(loc: Vec<f64>, kernel: K) -> Self {
         BasisFunction {
             loc: loc,
             kernel: kernel,
@@ -21,24 +22,34 @@ impl BasisFunction {
     }
 }
 
-impl Function<[f64], f64> for BasisFunction {
+impl<K: Kernel> Function<[f64], f64> for BasisFunction<K> {
     fn evaluate(&self, input: &[f64]) -> f64 {
         self.kernel.kernel(&self.loc, input)
     }
 }
 
 
-pub struct BasisNetwork {
-    bases: Vec<BasisFunction>,
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BasisNetwork<K: Kernel> {
+    bases: Vec<BasisFunction<K>>,
 }
 
-impl BasisNetwork {
-    pub fn new(bases: Vec<BasisFunction>) -> Self {
+impl<K: Kernel> BasisNetwork<K> {
+    pub fn new(bases: Vec<BasisFunction<K>>) -> Self {
         BasisNetwork { bases: bases }
     }
+
+    /// Constructs a network with a single, shared kernel centred at each row of `locs`.
+    pub fn from_centres(locs: &Array2<f64>, kernel: K) -> Self
+        where K: Clone
+    {
+        BasisNetwork {
+            bases: locs.outer_iter().map(|l| BasisFunction::new(l.to_vec(), kernel.clone())).collect(),
+        }
+    }
 }
 
-impl Projection<RegularSpace<Continuous>> for BasisNetwork {
+impl<K: Kernel + PartialEq> Projection<RegularSpace<Continuous>> for BasisNetwork<K> {
     fn project(&self, input: &Vec<f64>) -> Array1<f64> {
         Array1::from_shape_fn((self.bases.len(),), |i| self.bases[i].evaluate(input))
     }
@@ -50,14 +61,46 @@ impl Projection<RegularSpace<Continuous>> for BasisNetwork {
     }
 
     fn dim(&self) -> usize {
-        unimplemented!()
+        self.bases.first().map_or(0, |b| b.loc.len())
     }
 
     fn size(&self) -> usize {
         self.bases.len()
     }
 
-    fn equivalent(&self, _: &Self) -> bool {
-        unimplemented!()
+    fn equivalent(&self, other: &Self) -> bool {
+        self.bases == other.bases
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geometry::kernels::Gaussian;
+    use ndarray::arr2;
+    use serde_json;
+
+    #[test]
+    fn test_projection() {
+        let bn = BasisNetwork::from_centres(&arr2(&[[0.0], [1.0]]), Gaussian::default());
+
+        assert_eq!(bn.dim(), 1);
+        assert_eq!(bn.size(), 2);
+
+        let phi = bn.project(&vec![0.0]);
+
+        assert_eq!(phi[0], 1.0);
+        assert!((phi[1] - (-0.5f64).exp()).abs() < 1e-7);
+    }
+
+    #[test]
+    fn test_serialisation() {
+        let bn = BasisNetwork::from_centres(&arr2(&[[0.0, 1.0], [1.0, 0.0]]), Gaussian::default());
+        let json = serde_json::to_string(&bn).unwrap();
+
+        let bn2: BasisNetwork<Gaussian> = serde_json::from_str(&json).unwrap();
+
+        assert!(bn.equivalent(&bn2));
     }
 }
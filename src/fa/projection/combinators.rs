@@ -0,0 +1,236 @@
+use super::{Projection, SparseProjection};
+use geometry::Space;
+use ndarray::Array1;
+
+
+/// Concatenation of the features of two projections over the same input.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Stack<P1, P2> {
+    p1: P1,
+    p2: P2,
+}
+
+impl<P1, P2> Stack<P1, P2> {
+    pub fn new(p1: P1, p2: P2) -> Self {
+        Stack { p1: p1, p2: p2 }
+    }
+}
+
+impl<S: Space, P1: Projection<S>, P2: Projection<S>> Projection<S> for Stack<P1, P2> {
+    fn project_onto(&self, input: &S::Repr, phi: &mut Array1<f64>) {
+        let n1 = self.p1.size();
+
+        phi.slice_mut(s![..n1 as isize]).assign(&self.p1.project(input));
+        phi.slice_mut(s![n1 as isize..]).assign(&self.p2.project(input));
+    }
+
+    fn dim(&self) -> usize {
+        self.p1.dim()
+    }
+
+    fn size(&self) -> usize {
+        self.p1.size() + self.p2.size()
+    }
+
+    fn equivalent(&self, other: &Self) -> bool {
+        self.p1.equivalent(&other.p1) && self.p2.equivalent(&other.p2)
+    }
+}
+
+impl<S: Space, P1, P2> SparseProjection<S> for Stack<P1, P2>
+    where P1: SparseProjection<S>,
+          P2: SparseProjection<S>
+{
+    fn project_onto_sparse(&self, input: &S::Repr, indices: &mut Array1<usize>) {
+        let (n1, s1) = (self.p1.size(), self.p1.sparsity());
+
+        for (i, v) in self.p1.project_sparse(input).iter().enumerate() {
+            indices[i] = *v;
+        }
+
+        for (i, v) in self.p2.project_sparse(input).iter().enumerate() {
+            indices[s1 + i] = n1 + *v;
+        }
+    }
+
+    fn sparsity(&self) -> usize {
+        self.p1.sparsity() + self.p2.sparsity()
+    }
+}
+
+
+/// Tensor product of the features of two projections over the same input.
+///
+/// The feature at index `i*n2 + j` is `phi1[i]*phi2[j]`, where `n2` is the
+/// size of the second projection.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Product<P1, P2> {
+    p1: P1,
+    p2: P2,
+}
+
+impl<P1, P2> Product<P1, P2> {
+    pub fn new(p1: P1, p2: P2) -> Self {
+        Product { p1: p1, p2: p2 }
+    }
+}
+
+impl<S: Space, P1: Projection<S>, P2: Projection<S>> Projection<S> for Product<P1, P2> {
+    fn project_onto(&self, input: &S::Repr, phi: &mut Array1<f64>) {
+        let n2 = self.p2.size();
+
+        let phi1 = self.p1.project(input);
+        let phi2 = self.p2.project(input);
+
+        for (i, x) in phi1.iter().enumerate() {
+            for (j, y) in phi2.iter().enumerate() {
+                phi[i * n2 + j] = x * y;
+            }
+        }
+    }
+
+    fn dim(&self) -> usize {
+        self.p1.dim()
+    }
+
+    fn size(&self) -> usize {
+        self.p1.size() * self.p2.size()
+    }
+
+    fn equivalent(&self, other: &Self) -> bool {
+        self.p1.equivalent(&other.p1) && self.p2.equivalent(&other.p2)
+    }
+}
+
+impl<S: Space, P1, P2> SparseProjection<S> for Product<P1, P2>
+    where P1: SparseProjection<S>,
+          P2: SparseProjection<S>
+{
+    fn project_onto_sparse(&self, input: &S::Repr, indices: &mut Array1<usize>) {
+        let (n2, s2) = (self.p2.size(), self.p2.sparsity());
+
+        let idx1 = self.p1.project_sparse(input);
+        let idx2 = self.p2.project_sparse(input);
+
+        for (i, x) in idx1.iter().enumerate() {
+            for (j, y) in idx2.iter().enumerate() {
+                indices[i * s2 + j] = x * n2 + y;
+            }
+        }
+    }
+
+    fn sparsity(&self) -> usize {
+        self.p1.sparsity() * self.p2.sparsity()
+    }
+}
+
+
+/// Augments a projection with a constant, unit-valued bias feature.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct WithBias<P> {
+    projector: P,
+}
+
+impl<P> WithBias<P> {
+    pub fn new(projector: P) -> Self {
+        WithBias { projector: projector }
+    }
+}
+
+impl<S: Space, P: Projection<S>> Projection<S> for WithBias<P> {
+    fn project_onto(&self, input: &S::Repr, phi: &mut Array1<f64>) {
+        let n = self.projector.size();
+
+        phi.slice_mut(s![..n as isize]).assign(&self.projector.project(input));
+        phi[n] = 1.0;
+    }
+
+    fn dim(&self) -> usize {
+        self.projector.dim()
+    }
+
+    fn size(&self) -> usize {
+        self.projector.size() + 1
+    }
+
+    fn equivalent(&self, other: &Self) -> bool {
+        self.projector.equivalent(&other.projector)
+    }
+}
+
+impl<S: Space, P: SparseProjection<S>> SparseProjection<S> for WithBias<P> {
+    fn project_onto_sparse(&self, input: &S::Repr, indices: &mut Array1<usize>) {
+        let s = self.projector.sparsity();
+
+        for (i, v) in self.projector.project_sparse(input).iter().enumerate() {
+            indices[i] = *v;
+        }
+
+        indices[s] = self.projector.size();
+    }
+
+    fn sparsity(&self) -> usize {
+        self.projector.sparsity() + 1
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fa::projection::{Fourier, UniformGrid};
+    use geometry::RegularSpace;
+    use geometry::dimensions::Partitioned;
+    use ndarray::arr1;
+
+    fn make_grid() -> UniformGrid {
+        let mut ds = RegularSpace::new();
+        ds = ds.push(Partitioned::new(0.0, 1.0, 2));
+
+        UniformGrid::new(ds)
+    }
+
+    #[test]
+    fn test_stack() {
+        let p = Stack::new(Fourier::new(1, vec![(0.0, 1.0)]), make_grid());
+
+        assert_eq!(p.dim(), 1);
+        assert_eq!(p.size(), 4);
+        assert!(p.project(&vec![0.0]).all_close(&arr1(&[1.0, 1.0, 1.0, 0.0]), 1e-6));
+    }
+
+    #[test]
+    fn test_stack_sparse() {
+        let p = Stack::new(make_grid(), make_grid());
+
+        assert_eq!(p.sparsity(), 2);
+        assert_eq!(p.project_sparse(&vec![0.75]), arr1(&[1, 3]));
+    }
+
+    #[test]
+    fn test_product() {
+        let p = Product::new(Fourier::new(1, vec![(0.0, 1.0)]), make_grid());
+
+        assert_eq!(p.size(), 4);
+        assert!(p.project(&vec![1.0]).all_close(&arr1(&[0.0, 1.0, 0.0, -1.0]), 1e-6));
+    }
+
+    #[test]
+    fn test_product_sparse() {
+        let p = Product::new(make_grid(), make_grid());
+
+        assert_eq!(p.sparsity(), 1);
+        assert_eq!(p.project_sparse(&vec![0.75]), arr1(&[3]));
+        assert_eq!(p.project(&vec![0.25]), arr1(&[1.0, 0.0, 0.0, 0.0]));
+    }
+
+    #[test]
+    fn test_bias() {
+        let p = WithBias::new(make_grid());
+
+        assert_eq!(p.size(), 3);
+        assert_eq!(p.sparsity(), 2);
+        assert_eq!(p.project(&vec![0.25]), arr1(&[1.0, 0.0, 1.0]));
+        assert_eq!(p.project_sparse(&vec![0.75]), arr1(&[1, 2]));
+    }
+}
@@ -4,8 +4,14 @@ use geometry::RegularSpace;
 use geometry::dimensions::{BoundedDimension, Continuous};
 use ndarray::Array1;
 use utils::cartesian_product;
+use super::independent_coefficients;
 
 
+/// Fourier basis projection.
+///
+/// Konidaris, George, Sarah Osentoski, and Philip S. Thomas. "Value function
+/// approximation in reinforcement learning using the Fourier basis." AAAI.
+/// Vol. 6. 2011.
 #[derive(Serialize, Deserialize)]
 pub struct Fourier {
     order: u8,
@@ -28,6 +34,23 @@ impl Fourier {
     pub fn from_space(order: u8, input_space: RegularSpace<Continuous>) -> Self {
         Fourier::new(order, input_space.iter().map(|d| d.limits()).collect())
     }
+
+    /// Constructs a basis with no interaction terms between dimensions, such
+    /// that the number of features is `1 + order*dim` rather than
+    /// `(order+1)^dim`.
+    pub fn independent(order: u8, limits: Vec<(f64, f64)>) -> Self {
+        let coefficients = independent_coefficients(order, limits.len());
+
+        Fourier {
+            order: order,
+            limits: limits,
+            coefficients: coefficients,
+        }
+    }
+
+    pub fn independent_from_space(order: u8, input_space: RegularSpace<Continuous>) -> Self {
+        Fourier::independent(order, input_space.iter().map(|d| d.limits()).collect())
+    }
 }
 
 impl Projection<RegularSpace<Continuous>> for Fourier {
@@ -48,11 +71,12 @@ impl Projection<RegularSpace<Continuous>> for Fourier {
     }
 
     fn size(&self) -> usize {
-        (self.order as usize + 1).pow(self.dim() as u32)
+        self.coefficients.len()
     }
 
     fn equivalent(&self, other: &Self) -> bool {
-        self.order == other.order && self.limits == other.limits
+        self.order == other.order && self.limits == other.limits &&
+        self.coefficients == other.coefficients
     }
 }
 
@@ -81,4 +105,20 @@ mod tests {
         assert!(f.project(&vec![1.0/3.0]).all_close(&arr1(&vec![1.0, 0.5]), 1e-6));
         assert!(f.project(&vec![2.0/3.0]).all_close(&arr1(&vec![1.0, -0.5]), 1e-6));
     }
+
+    #[test]
+    fn test_independent() {
+        let f = Fourier::independent(3, vec![(0.0, 1.0); 4]);
+        let g = Fourier::new(3, vec![(0.0, 1.0); 4]);
+
+        assert_eq!(f.dim(), 4);
+        assert_eq!(f.size(), 13);
+        assert_eq!(g.size(), 256);
+        assert!(!f.equivalent(&g));
+
+        let phi = f.project(&vec![0.5, 0.0, 0.0, 0.0]);
+
+        assert!(phi.slice(s![0..4]).all_close(&arr1(&vec![1.0, 0.0, -1.0, 0.0]), 1e-6));
+        assert!(phi.slice(s![4..]).all_close(&Array1::from_elem((9,), 1.0), 1e-6));
+    }
 }
@@ -0,0 +1,335 @@
+use super::{Projection, SparseProjection};
+use geometry::{Space, RegularSpace};
+use geometry::dimensions::{BoundedDimension, Continuous};
+use ndarray::{Array1, Array2};
+use rand::{Rng, thread_rng};
+
+
+/// Rule used to decide which prototypes are active for a given input.
+pub trait Proximity {
+    /// Returns the indices of the prototypes (rows of `prototypes`) that are
+    /// active for `input`; both are rescaled onto the unit hypercube.
+    fn active(&self, prototypes: &Array2<f64>, input: &[f64]) -> Vec<usize>;
+
+    /// Checks that the rule is valid for a coding with `n_prototypes`.
+    fn check(&self, _n_prototypes: usize) {}
+}
+
+
+/// The `k` prototypes closest, in Euclidean distance, to the input are active.
+///
+/// Exactly `k` features are always active, so the resulting coding is also a
+/// `SparseProjection`.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct NearestK(pub usize);
+
+impl Proximity for NearestK {
+    fn active(&self, prototypes: &Array2<f64>, input: &[f64]) -> Vec<usize> {
+        let mut ds: Vec<(usize, f64)> = prototypes.outer_iter().enumerate().map(|(i, p)| {
+            (i, p.iter().zip(input.iter()).fold(0.0, |acc, (a, b)| acc + (a - b) * (a - b)))
+        }).collect();
+        ds.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+
+        ds.into_iter().take(self.0).map(|(i, _)| i).collect()
+    }
+
+    fn check(&self, n_prototypes: usize) {
+        if self.0 > n_prototypes {
+            panic!("Cannot activate {} of {} prototypes.", self.0, n_prototypes);
+        }
+    }
+}
+
+
+/// Each dimension is discretised into `resolution` bins, and a prototype is
+/// active if it differs from the input in at most `radius` of them.
+///
+/// The number of active features varies with the input, so the resulting
+/// coding only supports dense projection.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Hamming {
+    pub radius: usize,
+    pub resolution: usize,
+}
+
+impl Hamming {
+    pub fn new(radius: usize, resolution: usize) -> Self {
+        Hamming {
+            radius: radius,
+            resolution: resolution,
+        }
+    }
+
+    fn bin(&self, v: f64) -> isize {
+        let b = (v * self.resolution as f64).floor() as isize;
+
+        clip!(0isize, b, self.resolution as isize - 1)
+    }
+}
+
+impl Proximity for Hamming {
+    fn active(&self, prototypes: &Array2<f64>, input: &[f64]) -> Vec<usize> {
+        prototypes.outer_iter().enumerate().filter(|&(_, ref p)| {
+            p.iter().zip(input.iter()).filter(|&(a, b)| self.bin(*a) != self.bin(*b)).count() <=
+            self.radius
+        }).map(|(i, _)| i).collect()
+    }
+}
+
+
+/// Kanerva coding (sparse distributed memory) projection.
+///
+/// Features correspond to a set of prototype states, each of which is binary
+/// active or inactive depending on its proximity to the input. Prototypes may
+/// optionally be reallocated towards frequently visited regions of the space.
+///
+/// Visits are only counted through explicit calls to `observe`, such that
+/// projecting an input (e.g. for a bootstrap target) has no side effects.
+///
+/// Kanerva, Pentti. Sparse distributed memory. MIT press, 1988.
+///
+/// Ratitch, Bohdana, and Doina Precup. "Sparse distributed memories for
+/// on-line value-based reinforcement learning." European Conference on
+/// Machine Learning. Springer, Berlin, Heidelberg, 2004.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct KanervaCoding<P: Proximity = NearestK> {
+    limits: Vec<(f64, f64)>,
+    prototypes: Array2<f64>,
+    proximity: P,
+
+    // Prototypes rescaled onto the unit hypercube:
+    scaled: Array2<f64>,
+    visits: Vec<usize>,
+}
+
+impl<P: Proximity> KanervaCoding<P> {
+    pub fn new(limits: Vec<(f64, f64)>, prototypes: Array2<f64>, proximity: P) -> Self {
+        if prototypes.cols() != limits.len() {
+            panic!("Prototypes must have dimensionality {}.", limits.len());
+        }
+
+        proximity.check(prototypes.rows());
+
+        let n = prototypes.rows();
+        let scaled = Array2::from_shape_fn(prototypes.dim(), |(i, d)| {
+            rescale(limits[d], prototypes[(i, d)])
+        });
+
+        KanervaCoding {
+            limits: limits,
+            prototypes: prototypes,
+            proximity: proximity,
+
+            scaled: scaled,
+            visits: vec![0; n],
+        }
+    }
+
+    /// Constructs a coding with prototypes drawn uniformly within `limits`.
+    pub fn random(limits: Vec<(f64, f64)>, n_prototypes: usize, proximity: P) -> Self {
+        let mut rng = thread_rng();
+        let prototypes = Array2::from_shape_fn((n_prototypes, limits.len()), |(_, j)| {
+            let (lb, ub) = limits[j];
+
+            lb + (ub - lb) * rng.next_f64()
+        });
+
+        KanervaCoding::new(limits, prototypes, proximity)
+    }
+
+    /// Constructs a coding with prototypes sampled from `input_space`.
+    pub fn from_space(input_space: RegularSpace<Continuous>,
+                      n_prototypes: usize,
+                      proximity: P)
+                      -> Self {
+        let mut rng = thread_rng();
+        let limits: Vec<(f64, f64)> = input_space.iter().map(|d| d.limits()).collect();
+
+        let mut prototypes = Array2::zeros((n_prototypes, limits.len()));
+        for mut row in prototypes.outer_iter_mut() {
+            row.assign(&Array1::from_vec(input_space.sample(&mut rng)));
+        }
+
+        KanervaCoding::new(limits, prototypes, proximity)
+    }
+
+    pub fn prototypes(&self) -> &Array2<f64> {
+        &self.prototypes
+    }
+
+    /// Returns the number of times each prototype has been observed active
+    /// since the last call to `adapt`.
+    pub fn visits(&self) -> &[usize] {
+        &self.visits
+    }
+
+    /// Records a visit to each of the prototypes that are active for `input`.
+    pub fn observe(&mut self, input: &[f64]) {
+        for i in self.active(input) {
+            self.visits[i] += 1;
+        }
+    }
+
+    /// Moves each prototype activated fewer than `threshold` times to the
+    /// vicinity of the most frequently activated prototype, splitting the
+    /// latter's load between the two. All visit counts are reset afterwards.
+    pub fn adapt(&mut self, threshold: usize) {
+        let mut rng = thread_rng();
+        let mut visits = self.visits.clone();
+
+        for i in 0..visits.len() {
+            if visits[i] >= threshold {
+                continue;
+            }
+
+            let j = (0..visits.len()).fold(0, |acc, j| if visits[j] > visits[acc] { j } else { acc });
+            if visits[j] < threshold || i == j {
+                break;
+            }
+
+            for d in 0..self.limits.len() {
+                let (lb, ub) = self.limits[d];
+                let noise = 0.05 * (ub - lb) * (2.0 * rng.next_f64() - 1.0);
+
+                self.prototypes[(i, d)] = clip!(lb, self.prototypes[(j, d)] + noise, ub);
+                self.scaled[(i, d)] = rescale(self.limits[d], self.prototypes[(i, d)]);
+            }
+
+            visits[i] = visits[j] / 2;
+            visits[j] -= visits[i];
+        }
+
+        self.visits = vec![0; visits.len()];
+    }
+
+    fn active(&self, input: &[f64]) -> Vec<usize> {
+        let input: Vec<f64> =
+            input.iter().zip(self.limits.iter()).map(|(v, l)| rescale(*l, *v)).collect();
+
+        self.proximity.active(&self.scaled, &input)
+    }
+}
+
+fn rescale(limits: (f64, f64), v: f64) -> f64 {
+    (v - limits.0) / (limits.1 - limits.0)
+}
+
+impl<P: Proximity + PartialEq> Projection<RegularSpace<Continuous>> for KanervaCoding<P> {
+    fn project_onto(&self, input: &Vec<f64>, phi: &mut Array1<f64>) {
+        for i in self.active(input) {
+            phi[i] = 1.0;
+        }
+    }
+
+    fn dim(&self) -> usize {
+        self.limits.len()
+    }
+
+    fn size(&self) -> usize {
+        self.prototypes.rows()
+    }
+
+    fn equivalent(&self, other: &Self) -> bool {
+        self.limits == other.limits && self.proximity == other.proximity &&
+        self.prototypes == other.prototypes
+    }
+}
+
+impl SparseProjection<RegularSpace<Continuous>> for KanervaCoding<NearestK> {
+    fn project_onto_sparse(&self, input: &Vec<f64>, indices: &mut Array1<usize>) {
+        for (i, a) in self.active(input).into_iter().enumerate() {
+            indices[i] = a;
+        }
+    }
+
+    fn sparsity(&self) -> usize {
+        self.proximity.0
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::arr2;
+
+    fn make_kc<P: Proximity>(proximity: P) -> KanervaCoding<P> {
+        KanervaCoding::new(vec![(0.0, 10.0), (0.0, 10.0)],
+                           arr2(&[[1.0, 1.0], [1.5, 1.0], [9.0, 9.0], [5.0, 5.0]]),
+                           proximity)
+    }
+
+    #[test]
+    fn test_dimensions() {
+        let kc = make_kc(NearestK(2));
+
+        assert_eq!(kc.dim(), 2);
+        assert_eq!(kc.size(), 4);
+        assert_eq!(kc.sparsity(), 2);
+    }
+
+    #[test]
+    fn test_nearest_k() {
+        let kc = make_kc(NearestK(2));
+
+        let mut idx = kc.project_sparse(&vec![1.2, 1.0]).to_vec();
+        idx.sort();
+
+        assert_eq!(idx, vec![0, 1]);
+        assert_eq!(kc.project(&vec![8.0, 8.0]).to_vec(), vec![0.0, 0.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn test_hamming() {
+        let kc = make_kc(Hamming::new(1, 10));
+
+        assert_eq!(kc.project(&vec![1.2, 9.5]).to_vec(), vec![1.0, 1.0, 1.0, 0.0]);
+        assert_eq!(kc.project(&vec![5.5, 5.5]).to_vec(), vec![0.0, 0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_too_many_active() {
+        make_kc(NearestK(5));
+    }
+
+    #[test]
+    fn test_from_space() {
+        let space = RegularSpace::new()
+            .push(Continuous::new(-1.0, 1.0))
+            .push(Continuous::new(0.0, 5.0));
+        let kc = KanervaCoding::from_space(space, 50, NearestK(5));
+
+        assert_eq!(kc.size(), 50);
+
+        for p in kc.prototypes().outer_iter() {
+            assert!(p[0] >= -1.0 && p[0] <= 1.0);
+            assert!(p[1] >= 0.0 && p[1] <= 5.0);
+        }
+    }
+
+    #[test]
+    fn test_adapt() {
+        let mut kc = make_kc(NearestK(1));
+
+        // Projection alone does not count as a visit:
+        kc.project(&vec![1.0, 1.0]);
+        assert_eq!(kc.visits(), &[0; 4]);
+
+        for _ in 0..10 {
+            kc.observe(&[9.0, 9.0]);
+        }
+
+        assert_eq!(kc.visits(), &[0, 0, 10, 0]);
+
+        kc.adapt(1);
+
+        assert_eq!(kc.visits(), &[0; 4]);
+        assert!(kc.prototypes().row(0).all_close(&kc.prototypes().row(2), 0.5 + 1e-6));
+        assert!(kc.prototypes().row(3).all_close(&kc.prototypes().row(2), 0.5 + 1e-6));
+
+        // Moved prototypes are matched at their new locations:
+        let p0 = kc.prototypes().row(0).to_vec();
+        assert_eq!(kc.project(&p0)[0], 1.0);
+    }
+}
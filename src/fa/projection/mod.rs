@@ -28,15 +28,61 @@ pub trait SparseProjection<S: Space>: Projection<S> {
     fn sparsity(&self) -> usize;
 }
 
+/// An interface for projections onto state-action features φ(s, a).
+pub trait ActionProjection<S: Space> {
+    fn project_action_onto(&self, input: &S::Repr, action: usize, phi: &mut Array1<f64>);
+    fn project_action(&self, input: &S::Repr, action: usize) -> Array1<f64> {
+        let mut phi = Array1::zeros((self.size(),));
+        self.project_action_onto(input, action, &mut phi);
+
+        phi
+    }
+
+    fn n_actions(&self) -> usize;
+    fn size(&self) -> usize;
+}
+
+/// Coefficient vectors for a basis without interaction terms: the zero vector
+/// followed by `k*e_i` for each dimension `i` and `k` in `1..order+1`.
+fn independent_coefficients(order: u8, dim: usize) -> Vec<Vec<u8>> {
+    let mut coefficients = vec![vec![0; dim]];
+
+    for i in 0..dim {
+        for k in 1..(order + 1) {
+            let mut c = vec![0; dim];
+            c[i] = k;
+
+            coefficients.push(c);
+        }
+    }
+
+    coefficients
+}
+
 mod basis_network;
 pub use self::basis_network::*;
 
 mod rbf_network;
 pub use self::rbf_network::*;
 
+mod combinators;
+pub use self::combinators::*;
+
 mod fourier;
 pub use self::fourier::*;
 
+mod kanerva;
+pub use self::kanerva::*;
+
+mod normalisation;
+pub use self::normalisation::*;
+
+mod polynomial;
+pub use self::polynomial::*;
+
+mod state_action;
+pub use self::state_action::*;
+
 mod tile_coding;
 pub use self::tile_coding::*;
 
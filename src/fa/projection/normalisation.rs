@@ -0,0 +1,257 @@
+use super::{Projection, SparseProjection};
+use geometry::{Space, RegularSpace};
+use geometry::dimensions::{BoundedDimension, Continuous};
+use ndarray::Array1;
+
+
+/// Rescales each input dimension into the unit interval, using the bounds of
+/// the input space, before passing it on to the wrapped projection.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Rescaled<P> {
+    projector: P,
+    limits: Vec<(f64, f64)>,
+}
+
+impl<P> Rescaled<P> {
+    pub fn new(projector: P, limits: Vec<(f64, f64)>) -> Self {
+        Rescaled {
+            projector: projector,
+            limits: limits,
+        }
+    }
+
+    pub fn from_space(projector: P, input_space: RegularSpace<Continuous>) -> Self {
+        Rescaled::new(projector, input_space.iter().map(|d| d.limits()).collect())
+    }
+
+    fn rescale(&self, input: &Vec<f64>) -> Vec<f64> {
+        input.iter().zip(self.limits.iter()).map(|(v, &(lb, ub))| (v - lb) / (ub - lb)).collect()
+    }
+}
+
+impl<P: Projection<RegularSpace<Continuous>>> Projection<RegularSpace<Continuous>> for Rescaled<P> {
+    fn project_onto(&self, input: &Vec<f64>, phi: &mut Array1<f64>) {
+        self.projector.project_onto(&self.rescale(input), phi)
+    }
+
+    fn dim(&self) -> usize {
+        self.limits.len()
+    }
+
+    fn size(&self) -> usize {
+        self.projector.size()
+    }
+
+    fn equivalent(&self, other: &Self) -> bool {
+        self.limits == other.limits && self.projector.equivalent(&other.projector)
+    }
+}
+
+impl<P> SparseProjection<RegularSpace<Continuous>> for Rescaled<P>
+    where P: SparseProjection<RegularSpace<Continuous>>
+{
+    fn project_onto_sparse(&self, input: &Vec<f64>, indices: &mut Array1<usize>) {
+        self.projector.project_onto_sparse(&self.rescale(input), indices)
+    }
+
+    fn sparsity(&self) -> usize {
+        self.projector.sparsity()
+    }
+}
+
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct RunningStats {
+    count: usize,
+    mean: Array1<f64>,
+    m2: Array1<f64>,
+}
+
+impl RunningStats {
+    fn new(n: usize) -> Self {
+        RunningStats {
+            count: 0,
+            mean: Array1::zeros((n,)),
+            m2: Array1::zeros((n,)),
+        }
+    }
+
+    // Welford's online algorithm:
+    fn update(&mut self, x: &Array1<f64>) {
+        if self.count == 0 {
+            *self = RunningStats::new(x.len());
+        }
+
+        self.count += 1;
+
+        let delta = x - &self.mean;
+        self.mean.scaled_add(1.0 / self.count as f64, &delta);
+
+        let delta2 = x - &self.mean;
+        self.m2 += &(delta * delta2);
+    }
+
+    fn std(&self) -> Array1<f64> {
+        if self.count < 2 {
+            return Array1::from_elem(self.mean.len(), 1.0);
+        }
+
+        let n = self.count as f64;
+
+        self.m2.mapv(|v| {
+            let sd = (v / (n - 1.0)).sqrt();
+
+            if sd > 0.0 { sd } else { 1.0 }
+        })
+    }
+
+    fn standardise(&self, raw: &Array1<f64>, phi: &mut Array1<f64>) {
+        if self.count < 2 {
+            return phi.assign(raw);
+        }
+
+        let n = self.count as f64;
+
+        let stats = self.mean.iter().zip(self.m2.iter());
+
+        for ((p, x), (mean, m2)) in phi.iter_mut().zip(raw.iter()).zip(stats) {
+            let sd = (m2 / (n - 1.0)).sqrt();
+
+            // Constant features carry no scale information, so they are left
+            // untouched rather than being collapsed onto zero:
+            *p = if sd > 0.0 { (x - mean) / sd } else { *x };
+        }
+    }
+}
+
+
+/// Standardises the output of a projection to zero mean and unit variance
+/// using running estimates of the feature statistics.
+///
+/// The statistics are only updated through explicit calls to `observe`, once
+/// per input, such that projecting an input any number of times has no side
+/// effects. Features with zero variance are passed through unchanged.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Standardised<P> {
+    projector: P,
+    stats: RunningStats,
+    frozen: bool,
+}
+
+impl<P> Standardised<P> {
+    pub fn new(projector: P) -> Self {
+        Standardised {
+            projector: projector,
+            stats: RunningStats::new(0),
+            frozen: false,
+        }
+    }
+
+    /// Updates the running statistics with the raw features of `input`.
+    pub fn observe<S: Space>(&mut self, input: &S::Repr)
+        where P: Projection<S>
+    {
+        if !self.frozen {
+            let raw = self.projector.project(input);
+
+            self.stats.update(&raw);
+        }
+    }
+
+    /// Stops the running statistics from being updated.
+    pub fn freeze(&mut self) {
+        self.frozen = true;
+    }
+
+    pub fn unfreeze(&mut self) {
+        self.frozen = false;
+    }
+
+    pub fn mean(&self) -> Array1<f64> {
+        self.stats.mean.clone()
+    }
+
+    pub fn std(&self) -> Array1<f64> {
+        self.stats.std()
+    }
+}
+
+impl<S: Space, P: Projection<S>> Projection<S> for Standardised<P> {
+    fn project_onto(&self, input: &S::Repr, phi: &mut Array1<f64>) {
+        let raw = self.projector.project(input);
+
+        self.stats.standardise(&raw, phi);
+    }
+
+    fn dim(&self) -> usize {
+        self.projector.dim()
+    }
+
+    fn size(&self) -> usize {
+        self.projector.size()
+    }
+
+    fn equivalent(&self, other: &Self) -> bool {
+        self.projector.equivalent(&other.projector)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fa::projection::Fourier;
+    use ndarray::arr1;
+
+    #[test]
+    fn test_rescaled() {
+        let f = Fourier::new(1, vec![(0.0, 1.0)]);
+        let r = Rescaled::new(Fourier::new(1, vec![(0.0, 1.0)]), vec![(-10.0, 10.0)]);
+
+        assert_eq!(r.dim(), 1);
+        assert_eq!(r.size(), 2);
+
+        assert!(r.project(&vec![-10.0]).all_close(&f.project(&vec![0.0]), 1e-6));
+        assert!(r.project(&vec![0.0]).all_close(&f.project(&vec![0.5]), 1e-6));
+        assert!(r.project(&vec![10.0]).all_close(&f.project(&vec![1.0]), 1e-6));
+    }
+
+    #[test]
+    fn test_standardised() {
+        type Input = RegularSpace<Continuous>;
+
+        let mut p = Standardised::new(Fourier::new(1, vec![(0.0, 1.0)]));
+
+        for i in 0..1000 {
+            p.observe::<Input>(&vec![i as f64 / 1000.0]);
+        }
+
+        let mean = p.mean();
+        let std = p.std();
+
+        assert!((mean[0] - 1.0).abs() < 1e-6);
+        assert!(mean[1].abs() < 1e-2);
+
+        // Constant features are left unscaled:
+        assert_eq!(std[0], 1.0);
+        assert!((std[1] - 0.5f64.sqrt()).abs() < 1e-2);
+
+        // Projecting leaves the statistics untouched:
+        p.project(&vec![0.0]);
+        assert_eq!(p.mean(), mean);
+
+        p.freeze();
+        p.observe::<Input>(&vec![0.0]);
+
+        assert_eq!(p.mean(), mean);
+        assert!(p.project(&vec![0.5]).all_close(&arr1(&[1.0, -mean[1] / std[1]]), 1e-6));
+    }
+
+    #[test]
+    fn test_standardised_unobserved() {
+        let f = Fourier::new(1, vec![(0.0, 1.0)]);
+        let p = Standardised::new(Fourier::new(1, vec![(0.0, 1.0)]));
+
+        assert!(p.project(&vec![0.3]).all_close(&f.project(&vec![0.3]), 1e-6));
+    }
+}
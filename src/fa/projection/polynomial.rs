@@ -0,0 +1,115 @@
+use super::Projection;
+use geometry::RegularSpace;
+use geometry::dimensions::{BoundedDimension, Continuous};
+use ndarray::Array1;
+use utils::cartesian_product;
+use super::independent_coefficients;
+
+
+/// Polynomial basis projection.
+///
+/// Each feature is a monomial Π x_i^c_i over the inputs rescaled to the unit
+/// hypercube, with every exponent c_i in `0..order+1`.
+#[derive(Serialize, Deserialize)]
+pub struct Polynomial {
+    order: u8,
+    limits: Vec<(f64, f64)>,
+    exponents: Vec<Vec<u8>>,
+}
+
+impl Polynomial {
+    pub fn new(order: u8, limits: Vec<(f64, f64)>) -> Self {
+        let exponents =
+            cartesian_product(&vec![(0..(order+1)).collect::<Vec<u8>>(); limits.len()]);
+
+        Polynomial {
+            order: order,
+            limits: limits,
+            exponents: exponents,
+        }
+    }
+
+    pub fn from_space(order: u8, input_space: RegularSpace<Continuous>) -> Self {
+        Polynomial::new(order, input_space.iter().map(|d| d.limits()).collect())
+    }
+
+    /// Constructs a basis with no interaction terms between dimensions, such
+    /// that the number of features is `1 + order*dim` rather than
+    /// `(order+1)^dim`.
+    pub fn independent(order: u8, limits: Vec<(f64, f64)>) -> Self {
+        let exponents = independent_coefficients(order, limits.len());
+
+        Polynomial {
+            order: order,
+            limits: limits,
+            exponents: exponents,
+        }
+    }
+
+    pub fn independent_from_space(order: u8, input_space: RegularSpace<Continuous>) -> Self {
+        Polynomial::independent(order, input_space.iter().map(|d| d.limits()).collect())
+    }
+}
+
+impl Projection<RegularSpace<Continuous>> for Polynomial {
+    fn project_onto(&self, input: &Vec<f64>, phi: &mut Array1<f64>) {
+        let scaled_state = input.iter().enumerate().map(|(i, v)| {
+            (v - self.limits[i].0) / (self.limits[i].1 - self.limits[i].0)
+        }).collect::<Vec<f64>>();
+
+        for (i, exps) in self.exponents.iter().enumerate() {
+            phi[i] = scaled_state.iter().zip(exps).fold(1.0, |acc, (v, e)| acc * v.powi(*e as i32));
+        }
+    }
+
+    fn dim(&self) -> usize {
+        self.limits.len()
+    }
+
+    fn size(&self) -> usize {
+        self.exponents.len()
+    }
+
+    fn equivalent(&self, other: &Self) -> bool {
+        self.order == other.order && self.limits == other.limits &&
+        self.exponents == other.exponents
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::arr1;
+
+    #[test]
+    fn test_order2_1d() {
+        let p = Polynomial::new(2, vec![(-1.0, 1.0)]);
+
+        assert_eq!(p.dim(), 1);
+        assert_eq!(p.size(), 3);
+
+        assert!(p.project(&vec![-1.0]).all_close(&arr1(&vec![1.0, 0.0, 0.0]), 1e-6));
+        assert!(p.project(&vec![0.0]).all_close(&arr1(&vec![1.0, 0.5, 0.25]), 1e-6));
+        assert!(p.project(&vec![1.0]).all_close(&arr1(&vec![1.0, 1.0, 1.0]), 1e-6));
+    }
+
+    #[test]
+    fn test_order1_2d() {
+        let p = Polynomial::new(1, vec![(0.0, 1.0), (0.0, 1.0)]);
+
+        assert_eq!(p.size(), 4);
+        assert!((p.project(&vec![0.5, 0.25]).scalar_sum() - 1.875).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_independent() {
+        let p = Polynomial::independent(2, vec![(0.0, 1.0); 3]);
+
+        assert_eq!(p.dim(), 3);
+        assert_eq!(p.size(), 7);
+
+        assert!(p.project(&vec![0.5, 1.0, 0.0])
+                    .all_close(&arr1(&vec![1.0, 0.5, 0.25, 1.0, 1.0, 0.0, 0.0]), 1e-6));
+    }
+}
@@ -0,0 +1,114 @@
+use super::{Projection, ActionProjection};
+use geometry::{Space, RegularSpace};
+use geometry::dimensions::Continuous;
+use ndarray::Array1;
+
+
+/// State-action features formed by copying the state features into the block
+/// of the chosen action, with all other blocks set to zero.
+///
+/// Feature `i` of action `a` is stored at index `i * n_actions + a`, which
+/// coincides with the (row-major) layout of the weights of a `DenseLinear`
+/// approximator with `n_actions` outputs.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PerAction<P> {
+    projector: P,
+    n_actions: usize,
+}
+
+impl<P> PerAction<P> {
+    pub fn new(projector: P, n_actions: usize) -> Self {
+        PerAction {
+            projector: projector,
+            n_actions: n_actions,
+        }
+    }
+}
+
+impl<S: Space, P: Projection<S>> ActionProjection<S> for PerAction<P> {
+    fn project_action_onto(&self, input: &S::Repr, action: usize, phi: &mut Array1<f64>) {
+        for (i, v) in self.projector.project(input).iter().enumerate() {
+            phi[i * self.n_actions + action] = *v;
+        }
+    }
+
+    fn n_actions(&self) -> usize {
+        self.n_actions
+    }
+
+    fn size(&self) -> usize {
+        self.projector.size() * self.n_actions
+    }
+}
+
+
+/// State-action features obtained by appending the action index to the state
+/// as an extra input dimension.
+///
+/// This allows any projection over continuous spaces to generalise across
+/// actions, e.g. tile coding over the joint state-action space.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ActionInput<P> {
+    projector: P,
+    n_actions: usize,
+}
+
+impl<P> ActionInput<P> {
+    pub fn new(projector: P, n_actions: usize) -> Self {
+        ActionInput {
+            projector: projector,
+            n_actions: n_actions,
+        }
+    }
+}
+
+impl<P: Projection<RegularSpace<Continuous>>> ActionProjection<RegularSpace<Continuous>>
+    for ActionInput<P> {
+    fn project_action_onto(&self, input: &Vec<f64>, action: usize, phi: &mut Array1<f64>) {
+        let mut joint = input.clone();
+        joint.push(action as f64);
+
+        self.projector.project_onto(&joint, phi);
+    }
+
+    fn n_actions(&self) -> usize {
+        self.n_actions
+    }
+
+    fn size(&self) -> usize {
+        self.projector.size()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fa::projection::UniformGrid;
+    use geometry::dimensions::Partitioned;
+    use ndarray::arr1;
+
+    #[test]
+    fn test_per_action() {
+        let grid = UniformGrid::new(RegularSpace::new().push(Partitioned::new(0.0, 1.0, 2)));
+        let p = PerAction::new(grid, 3);
+
+        assert_eq!(p.n_actions(), 3);
+        assert_eq!(ActionProjection::<RegularSpace<Continuous>>::size(&p), 6);
+
+        assert_eq!(p.project_action(&vec![0.25], 0), arr1(&[1.0, 0.0, 0.0, 0.0, 0.0, 0.0]));
+        assert_eq!(p.project_action(&vec![0.75], 2), arr1(&[0.0, 0.0, 0.0, 0.0, 0.0, 1.0]));
+    }
+
+    #[test]
+    fn test_action_input() {
+        let grid = UniformGrid::new(RegularSpace::new()
+            .push(Partitioned::new(0.0, 1.0, 2))
+            .push(Partitioned::new(-0.5, 1.5, 2)));
+        let p = ActionInput::new(grid, 2);
+
+        assert_eq!(p.size(), 4);
+        assert_eq!(p.project_action(&vec![0.25], 0), arr1(&[1.0, 0.0, 0.0, 0.0]));
+        assert_eq!(p.project_action(&vec![0.75], 1), arr1(&[0.0, 0.0, 0.0, 1.0]));
+    }
+}
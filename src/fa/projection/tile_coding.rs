@@ -1,24 +1,216 @@
+use std::collections::HashMap;
 use std::hash::{Hasher, BuildHasher};
 use super::{Projection, SparseProjection};
 use geometry::RegularSpace;
-use geometry::dimensions::Continuous;
+use geometry::dimensions::{BoundedDimension, Continuous};
 use ndarray::Array1;
 
 
+/// Index table that assigns tiles to memory locations on a first come, first
+/// served basis; falls back to hashing (and counts the collisions) once full.
+///
+/// Tiles are only assigned through `TileCoding::allocate`; unassigned tiles
+/// are looked up by hashing, without modifying the table.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CollisionTable {
+    size: usize,
+    n_collisions: usize,
+
+    #[serde(with = "::utils::map_as_pairs")]
+    dictionary: HashMap<Vec<i64>, usize>,
+}
+
+impl CollisionTable {
+    pub fn new(size: usize) -> Self {
+        CollisionTable {
+            size: size,
+            n_collisions: 0,
+            dictionary: HashMap::new(),
+        }
+    }
+
+    /// Returns the number of tiles that have been assigned a unique index.
+    pub fn count(&self) -> usize {
+        self.dictionary.len()
+    }
+
+    /// Returns the number of tiles that were hashed after the table filled up.
+    pub fn collisions(&self) -> usize {
+        self.n_collisions
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.count() >= self.size
+    }
+
+    fn get<H: BuildHasher>(&self, coords: &[i64], hasher_builder: &H) -> usize {
+        match self.dictionary.get(coords) {
+            Some(&i) => i,
+            None => hash_coords(coords, hasher_builder) % self.size,
+        }
+    }
+
+    fn insert(&mut self, coords: Vec<i64>) {
+        if self.dictionary.contains_key(&coords) {
+            return;
+        }
+
+        if self.is_full() {
+            self.n_collisions += 1;
+        } else {
+            let i = self.count();
+            self.dictionary.insert(coords, i);
+        }
+    }
+}
+
+
+fn hash_coords<H: BuildHasher>(coords: &[i64], hasher_builder: &H) -> usize {
+    let mut hasher = hasher_builder.build_hasher();
+
+    for c in coords {
+        hasher.write_i64(*c);
+    }
+
+    hasher.finish() as usize
+}
+
+fn div_floor(a: i64, b: i64) -> i64 {
+    if a >= 0 { a / b } else { (a - b + 1) / b }
+}
+
+
+/// Sutton-style tile coding.
+///
+/// Each input dimension is partitioned into `n_tiles` tiles spanning its
+/// limits, and `n_tilings` such partitions are overlaid with offsets given by
+/// a displacement vector (defaulting to the asymmetric `[1, 3, 5, ...]`).
+/// Dimensions may optionally wrap around, e.g. for angles, and tiles may be
+/// mapped to memory either by hashing or via a `CollisionTable`. In the latter
+/// case, tiles are assigned through explicit calls to `allocate`, such that
+/// projection itself is stateless.
+///
+/// See http://incompleteideas.net/tiles/tiles3.html for details.
 #[derive(Serialize, Deserialize)]
 pub struct TileCoding<H: BuildHasher> {
     hasher_builder: H,
     n_tilings: usize,
     memory_size: usize,
+
+    limits: Vec<(f64, f64)>,
+    n_tiles: Vec<usize>,
+    wrap_widths: Vec<Option<usize>>,
+    displacement: Vec<usize>,
+
+    collision_table: Option<CollisionTable>,
 }
 
 impl<H: BuildHasher> TileCoding<H> {
-    pub fn new(hasher_builder: H, n_tilings: usize, memory_size: usize) -> Self {
+    pub fn new(hasher_builder: H,
+               limits: Vec<(f64, f64)>,
+               n_tiles: Vec<usize>,
+               n_tilings: usize,
+               memory_size: usize)
+               -> Self {
+        if limits.len() != n_tiles.len() {
+            panic!("Limits ({}) and number of tiles ({}) must have equal length.",
+                   limits.len(),
+                   n_tiles.len());
+        }
+
+        let n_dims = limits.len();
+
         TileCoding {
             hasher_builder: hasher_builder,
             n_tilings: n_tilings,
             memory_size: memory_size,
+
+            limits: limits,
+            n_tiles: n_tiles,
+            wrap_widths: vec![None; n_dims],
+            displacement: (0..n_dims).map(|i| 2 * i + 1).collect(),
+
+            collision_table: None,
+        }
+    }
+
+    pub fn from_space(hasher_builder: H,
+                      input_space: RegularSpace<Continuous>,
+                      n_tiles: usize,
+                      n_tilings: usize,
+                      memory_size: usize)
+                      -> Self {
+        let limits: Vec<(f64, f64)> = input_space.iter().map(|d| d.limits()).collect();
+        let n_tiles = vec![n_tiles; limits.len()];
+
+        TileCoding::new(hasher_builder, limits, n_tiles, n_tilings, memory_size)
+    }
+
+    /// Marks a dimension as cyclic such that its upper and lower limits coincide.
+    pub fn with_wrapping(mut self, dim: usize) -> Self {
+        self.wrap_widths[dim] = Some(self.n_tiles[dim]);
+        self
+    }
+
+    /// Overrides the default displacement vector used to offset each tiling.
+    pub fn with_displacement(mut self, displacement: Vec<usize>) -> Self {
+        if displacement.len() != self.limits.len() {
+            panic!("Displacement vector must have length {}.", self.limits.len());
         }
+
+        self.displacement = displacement;
+        self
+    }
+
+    /// Uses a collision-counting index table instead of pure hashing.
+    pub fn with_collision_table(mut self) -> Self {
+        self.collision_table = Some(CollisionTable::new(self.memory_size));
+        self
+    }
+
+    pub fn collision_table(&self) -> Option<&CollisionTable> {
+        self.collision_table.as_ref()
+    }
+
+    /// Assigns memory locations in the collision table to the tiles active for
+    /// `input`; a no-op when tiles are hashed.
+    pub fn allocate(&mut self, input: &[f64]) {
+        if self.collision_table.is_none() {
+            return;
+        }
+
+        let coords: Vec<Vec<i64>> =
+            (0..self.n_tilings).map(|t| self.tile_coords(input, t)).collect();
+
+        if let Some(ref mut ct) = self.collision_table {
+            for c in coords {
+                ct.insert(c);
+            }
+        }
+    }
+
+    fn tile_coords(&self, input: &[f64], tiling: usize) -> Vec<i64> {
+        let n_tilings = self.n_tilings as i64;
+
+        let mut coords = Vec::with_capacity(input.len() + 1);
+        coords.push(tiling as i64);
+
+        for (i, v) in input.iter().enumerate() {
+            let (lb, ub) = self.limits[i];
+
+            let scaled = (v - lb) * self.n_tiles[i] as f64 / (ub - lb);
+            let q = (scaled * self.n_tilings as f64).floor() as i64;
+            let b = (tiling * self.displacement[i]) as i64 % n_tilings;
+
+            let c = div_floor(q + b, n_tilings);
+
+            coords.push(match self.wrap_widths[i] {
+                Some(w) => ((c % w as i64) + w as i64) % w as i64,
+                None => c,
+            });
+        }
+
+        coords
     }
 }
 
@@ -30,7 +222,7 @@ impl<H: BuildHasher> Projection<RegularSpace<Continuous>> for TileCoding<H> {
     }
 
     fn dim(&self) -> usize {
-        unimplemented!()
+        self.limits.len()
     }
 
     fn size(&self) -> usize {
@@ -39,25 +231,20 @@ impl<H: BuildHasher> Projection<RegularSpace<Continuous>> for TileCoding<H> {
 
     fn equivalent(&self, other: &Self) -> bool {
         self.size() == other.size() && self.n_tilings == other.n_tilings &&
-        self.memory_size == other.memory_size
+        self.limits == other.limits && self.n_tiles == other.n_tiles &&
+        self.wrap_widths == other.wrap_widths && self.displacement == other.displacement
     }
 }
 
 impl<H: BuildHasher> SparseProjection<RegularSpace<Continuous>> for TileCoding<H> {
     fn project_onto_sparse(&self, input: &Vec<f64>, indices: &mut Array1<usize>) {
-        let mut hasher = self.hasher_builder.build_hasher();
-
-        let n_floats = input.len();
-        let floats: Vec<usize> =
-            input.iter().map(|f| (*f * self.n_tilings as f64).floor() as usize).collect();
-
         for t in 0..self.n_tilings {
-            hasher.write_usize(t);
-            for i in 0..n_floats {
-                hasher.write_usize((floats[i] + t + i*t*2) / self.n_tilings)
-            }
+            let coords = self.tile_coords(input, t);
 
-            indices[t] = hasher.finish() as usize % self.memory_size;
+            indices[t] = match self.collision_table {
+                Some(ref ct) => ct.get(&coords, &self.hasher_builder),
+                None => hash_coords(&coords, &self.hasher_builder) % self.memory_size,
+            };
         }
     }
 
@@ -65,3 +252,109 @@ impl<H: BuildHasher> SparseProjection<RegularSpace<Continuous>> for TileCoding<H
         self.n_tilings
     }
 }
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::BuildHasherDefault;
+
+    type DefaultBuildHasher = BuildHasherDefault<DefaultHasher>;
+
+    fn make_tc(n_tiles: usize, n_tilings: usize) -> TileCoding<DefaultBuildHasher> {
+        TileCoding::new(DefaultBuildHasher::default(),
+                        vec![(0.0, 1.0), (-10.0, 10.0)],
+                        vec![n_tiles; 2],
+                        n_tilings,
+                        4096)
+            .with_collision_table()
+    }
+
+    fn n_shared(x: &Array1<usize>, y: &Array1<usize>) -> usize {
+        x.iter().filter(|i| y.iter().any(|j| *i == j)).count()
+    }
+
+    #[test]
+    fn test_dimensions() {
+        let tc = make_tc(10, 8);
+
+        assert_eq!(tc.dim(), 2);
+        assert_eq!(tc.size(), 4096);
+        assert_eq!(tc.sparsity(), 8);
+    }
+
+    #[test]
+    fn test_deterministic() {
+        let tc = make_tc(10, 8);
+
+        assert_eq!(tc.project_sparse(&vec![0.5, 0.0]), tc.project_sparse(&vec![0.5, 0.0]));
+        assert_eq!(tc.project(&vec![0.5, 0.0]).scalar_sum(), 8.0);
+    }
+
+    #[test]
+    fn test_scaling() {
+        let tc = make_tc(10, 8);
+
+        // A shift of less than one tile width should share some, but not all, tiles:
+        let a = tc.project_sparse(&vec![0.5, 0.0]);
+        let b = tc.project_sparse(&vec![0.55, 1.0]);
+        let c = tc.project_sparse(&vec![0.95, 9.0]);
+
+        let ab = n_shared(&a, &b);
+
+        assert!(ab > 0 && ab < 8);
+        assert_eq!(n_shared(&a, &c), 0);
+    }
+
+    #[test]
+    fn test_displacement() {
+        let tc = TileCoding::new(DefaultBuildHasher::default(),
+                                 vec![(0.0, 1.0), (0.0, 1.0)],
+                                 vec![4, 4],
+                                 4,
+                                 1024);
+
+        // Tiling t is offset by (t * d mod 4) / 4 of a tile along a dimension
+        // with displacement d, i.e. by [1, 3] / 4 for tiling 1:
+        let coords: Vec<Vec<i64>> = (0..4).map(|t| tc.tile_coords(&[0.15, 0.15], t)).collect();
+
+        assert_eq!(coords, vec![vec![0, 0, 0], vec![1, 0, 1], vec![2, 1, 1], vec![3, 1, 0]]);
+
+        let tc = tc.with_displacement(vec![1, 1]);
+        let coords: Vec<Vec<i64>> = (0..4).map(|t| tc.tile_coords(&[0.15, 0.15], t)).collect();
+
+        assert_eq!(coords, vec![vec![0, 0, 0], vec![1, 0, 0], vec![2, 1, 1], vec![3, 1, 1]]);
+    }
+
+    #[test]
+    fn test_wrapping() {
+        let tc = TileCoding::new(DefaultBuildHasher::default(), vec![(0.0, 1.0)], vec![4], 4, 1024)
+            .with_wrapping(0)
+            .with_collision_table();
+
+        assert_eq!(tc.project_sparse(&vec![0.0]), tc.project_sparse(&vec![1.0]));
+        assert_eq!(tc.project_sparse(&vec![0.1]), tc.project_sparse(&vec![1.1]));
+    }
+
+    #[test]
+    fn test_collision_table() {
+        let mut tc = TileCoding::new(DefaultBuildHasher::default(), vec![(0.0, 1.0)], vec![4], 2, 4)
+            .with_collision_table();
+
+        // Projection alone does not allocate:
+        tc.project_sparse(&vec![0.1]);
+        assert_eq!(tc.collision_table().unwrap().count(), 0);
+
+        tc.allocate(&[0.1]);
+        assert_eq!(tc.collision_table().unwrap().count(), 2);
+        assert_eq!(tc.collision_table().unwrap().collisions(), 0);
+        assert_eq!(tc.project_sparse(&vec![0.1]).to_vec(), vec![0, 1]);
+
+        tc.allocate(&[0.9]);
+        assert!(tc.collision_table().unwrap().is_full());
+
+        tc.allocate(&[0.5]);
+        assert_eq!(tc.collision_table().unwrap().collisions(), 2);
+    }
+}
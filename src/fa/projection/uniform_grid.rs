@@ -1,4 +1,4 @@
-use super::Projection;
+use super::{Projection, SparseProjection};
 use geometry::{Space, RegularSpace};
 use geometry::dimensions::{Dimension, Continuous, Partitioned};
 use ndarray::Array1;
@@ -49,6 +49,16 @@ impl Projection<RegularSpace<Continuous>> for UniformGrid {
     }
 }
 
+impl SparseProjection<RegularSpace<Continuous>> for UniformGrid {
+    fn project_onto_sparse(&self, input: &Vec<f64>, indices: &mut Array1<usize>) {
+        indices[0] = self.hash(input);
+    }
+
+    fn sparsity(&self) -> usize {
+        1
+    }
+}
+
 
 #[cfg(test)]
 mod tests {
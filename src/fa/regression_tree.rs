@@ -0,0 +1,401 @@
+use super::{Function, Parameterised, VFunction, QFunction, Projection, SparseProjection};
+use geometry::RegularSpace;
+use geometry::dimensions::{BoundedDimension, Continuous};
+use ndarray::{Array1, Array2};
+
+
+/// Error statistics of the two halves of a cell along a single dimension.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+struct SplitStats {
+    n_lower: f64,
+    sum_lower: f64,
+
+    n_upper: f64,
+    sum_upper: f64,
+}
+
+impl SplitStats {
+    fn push(&mut self, lower: bool, error: f64) {
+        if lower {
+            self.n_lower += 1.0;
+            self.sum_lower += error;
+        } else {
+            self.n_upper += 1.0;
+            self.sum_upper += error;
+        }
+    }
+
+    /// Reduction in the variance of the errors obtained by splitting the cell
+    /// in half, i.e. `n_l * n_u / n^2 * (mean_l - mean_u)^2`.
+    fn variance_reduction(&self) -> f64 {
+        if self.n_lower == 0.0 || self.n_upper == 0.0 {
+            return 0.0;
+        }
+
+        let n = self.n_lower + self.n_upper;
+        let diff = self.sum_lower / self.n_lower - self.sum_upper / self.n_upper;
+
+        self.n_lower * self.n_upper / (n * n) * diff * diff
+    }
+}
+
+
+#[derive(Clone, Serialize, Deserialize)]
+enum Node {
+    Leaf {
+        index: usize,
+        bounds: Vec<(f64, f64)>,
+        stats: Vec<SplitStats>,
+    },
+    Split {
+        dim: usize,
+        threshold: f64,
+        lower: usize,
+        upper: usize,
+    },
+}
+
+
+/// Variable resolution partitioning of the input space using an incrementally
+/// grown kd-tree.
+///
+/// Each leaf of the tree is a hyperrectangular cell holding one value per
+/// output. Leaves track the errors they receive in each half of the cell along
+/// every dimension; once a leaf has seen `min_samples` errors, it is split at
+/// the midpoint of the dimension for which doing so most reduces the error
+/// variance, provided the reduction exceeds `min_reduction`. Children inherit
+/// the values of their parent.
+///
+/// As a projection, the tree maps an input onto a one-hot vector over its
+/// leaves. The number of features is fixed at `max_leaves`; on a split the
+/// lower child retains the feature index of its parent.
+///
+/// Munos, Remi, and Andrew Moore. "Variable resolution discretization in
+/// optimal control." Machine learning 49.2-3 (2002): 291-323.
+///
+/// Uther, William TB, and Manuela M. Veloso. "Tree based discretization for
+/// continuous state space reinforcement learning." AAAI/IAAI. 1998.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RegressionTree {
+    pub values: Array2<f64>,
+
+    pub min_samples: usize,
+    pub min_reduction: f64,
+
+    nodes: Vec<Node>,
+    n_dims: usize,
+    n_leaves: usize,
+}
+
+impl RegressionTree {
+    pub fn new(limits: Vec<(f64, f64)>, n_outputs: usize, max_leaves: usize) -> Self {
+        if max_leaves == 0 {
+            panic!("A regression tree requires at least one leaf.");
+        }
+
+        let n_dims = limits.len();
+
+        RegressionTree {
+            values: Array2::zeros((max_leaves, n_outputs)),
+
+            min_samples: 100,
+            min_reduction: 1e-3,
+
+            nodes: vec![Node::Leaf {
+                            index: 0,
+                            bounds: limits,
+                            stats: vec![SplitStats::default(); n_dims],
+                        }],
+            n_dims: n_dims,
+            n_leaves: 1,
+        }
+    }
+
+    pub fn from_space(input_space: RegularSpace<Continuous>,
+                      n_outputs: usize,
+                      max_leaves: usize)
+                      -> Self {
+        RegressionTree::new(input_space.iter().map(|d| d.limits()).collect(),
+                            n_outputs,
+                            max_leaves)
+    }
+
+    pub fn with_split_criterion(mut self, min_samples: usize, min_reduction: f64) -> Self {
+        self.min_samples = min_samples;
+        self.min_reduction = min_reduction;
+        self
+    }
+
+    pub fn n_leaves(&self) -> usize {
+        self.n_leaves
+    }
+
+    pub fn max_leaves(&self) -> usize {
+        self.values.rows()
+    }
+
+    /// Returns the feature index of the leaf containing `input`.
+    pub fn leaf_index(&self, input: &[f64]) -> usize {
+        match self.nodes[self.locate(input)] {
+            Node::Leaf { index, .. } => index,
+            Node::Split { .. } => unreachable!(),
+        }
+    }
+
+    /// Records the error observed at `input` and splits the containing leaf
+    /// if warranted.
+    ///
+    /// This is called by the `Parameterised` implementations, but may also be
+    /// used directly when the tree serves only as a projection.
+    pub fn observe(&mut self, input: &[f64], error: f64) {
+        let id = self.locate(input);
+        let min_samples = self.min_samples;
+
+        let ready = match self.nodes[id] {
+            Node::Leaf { ref bounds, ref mut stats, .. } => {
+                for (d, s) in stats.iter_mut().enumerate() {
+                    let (lb, ub) = bounds[d];
+
+                    s.push(input[d] < (lb + ub) / 2.0, error);
+                }
+
+                stats.first().map_or(false, |s| (s.n_lower + s.n_upper) as usize >= min_samples)
+            },
+            Node::Split { .. } => unreachable!(),
+        };
+
+        if ready && self.n_leaves < self.max_leaves() {
+            self.try_split(id);
+        }
+    }
+
+    fn locate(&self, input: &[f64]) -> usize {
+        let mut id = 0;
+
+        while let Node::Split { dim, threshold, lower, upper } = self.nodes[id] {
+            id = if input[dim] < threshold { lower } else { upper };
+        }
+
+        id
+    }
+
+    fn try_split(&mut self, id: usize) {
+        let (index, bounds, dim) = match self.nodes[id] {
+            Node::Leaf { index, ref bounds, ref stats } => {
+                let (dim, reduction) = stats.iter()
+                    .map(|s| s.variance_reduction())
+                    .enumerate()
+                    .fold((0, 0.0), |best, (d, r)| if r > best.1 { (d, r) } else { best });
+
+                if reduction <= self.min_reduction {
+                    return;
+                }
+
+                (index, bounds.clone(), dim)
+            },
+            Node::Split { .. } => unreachable!(),
+        };
+
+        let threshold = (bounds[dim].0 + bounds[dim].1) / 2.0;
+        let new_index = self.n_leaves;
+
+        let parent_values = self.values.row(index).to_owned();
+        self.values.row_mut(new_index).assign(&parent_values);
+
+        let mut lower_bounds = bounds.clone();
+        let mut upper_bounds = bounds;
+        lower_bounds[dim].1 = threshold;
+        upper_bounds[dim].0 = threshold;
+
+        let n_dims = self.n_dims;
+        let (lower, upper) = (self.nodes.len(), self.nodes.len() + 1);
+
+        self.nodes.push(Node::Leaf {
+            index: index,
+            bounds: lower_bounds,
+            stats: vec![SplitStats::default(); n_dims],
+        });
+        self.nodes.push(Node::Leaf {
+            index: new_index,
+            bounds: upper_bounds,
+            stats: vec![SplitStats::default(); n_dims],
+        });
+
+        self.nodes[id] = Node::Split {
+            dim: dim,
+            threshold: threshold,
+            lower: lower,
+            upper: upper,
+        };
+        self.n_leaves += 1;
+    }
+}
+
+impl Function<Vec<f64>, f64> for RegressionTree {
+    fn evaluate(&self, input: &Vec<f64>) -> f64 {
+        self.values[(self.leaf_index(input), 0)]
+    }
+}
+
+impl Function<Vec<f64>, Vec<f64>> for RegressionTree {
+    fn evaluate(&self, input: &Vec<f64>) -> Vec<f64> {
+        self.values.row(self.leaf_index(input)).to_vec()
+    }
+}
+
+impl Parameterised<Vec<f64>, f64> for RegressionTree {
+    fn update(&mut self, input: &Vec<f64>, error: f64) {
+        let i = self.leaf_index(input);
+        self.values[(i, 0)] += error;
+
+        self.observe(input, error);
+    }
+}
+
+impl Parameterised<Vec<f64>, Vec<f64>> for RegressionTree {
+    fn update(&mut self, input: &Vec<f64>, errors: Vec<f64>) {
+        let i = self.leaf_index(input);
+
+        for (v, e) in self.values.row_mut(i).iter_mut().zip(errors.iter()) {
+            *v += *e;
+        }
+
+        self.observe(input, errors.iter().sum());
+    }
+}
+
+impl VFunction<RegularSpace<Continuous>> for RegressionTree {}
+
+impl QFunction<RegularSpace<Continuous>> for RegressionTree {
+    fn evaluate_action(&self, input: &Vec<f64>, action: usize) -> f64 {
+        self.values[(self.leaf_index(input), action)]
+    }
+
+    fn update_action(&mut self, input: &Vec<f64>, action: usize, error: f64) {
+        let i = self.leaf_index(input);
+        self.values[(i, action)] += error;
+
+        self.observe(input, error);
+    }
+}
+
+impl Projection<RegularSpace<Continuous>> for RegressionTree {
+    fn project_onto(&self, input: &Vec<f64>, phi: &mut Array1<f64>) {
+        phi[self.leaf_index(input)] = 1.0;
+    }
+
+    fn dim(&self) -> usize {
+        self.n_dims
+    }
+
+    fn size(&self) -> usize {
+        self.max_leaves()
+    }
+
+    fn equivalent(&self, other: &Self) -> bool {
+        self.dim() == other.dim() && self.size() == other.size()
+    }
+}
+
+impl SparseProjection<RegularSpace<Continuous>> for RegressionTree {
+    fn project_onto_sparse(&self, input: &Vec<f64>, indices: &mut Array1<usize>) {
+        indices[0] = self.leaf_index(input);
+    }
+
+    fn sparsity(&self) -> usize {
+        1
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::RegressionTree;
+    use fa::{Function, Parameterised, Projection};
+    use geometry::RegularSpace;
+    use geometry::dimensions::Continuous;
+
+    fn value(tree: &RegressionTree, x: f64, y: f64) -> f64 {
+        tree.evaluate(&vec![x, y])
+    }
+
+    #[test]
+    fn test_single_leaf() {
+        let tree = RegressionTree::new(vec![(0.0, 1.0), (0.0, 1.0)], 1, 8);
+
+        assert_eq!(tree.n_leaves(), 1);
+        assert_eq!(tree.dim(), 2);
+        assert_eq!(tree.size(), 8);
+        assert_eq!(tree.project(&vec![0.3, 0.9])[0], 1.0);
+    }
+
+    #[test]
+    fn test_from_space() {
+        let space = RegularSpace::new()
+            .push(Continuous::new(-1.0, 1.0))
+            .push(Continuous::new(0.0, 10.0));
+        let mut tree = RegressionTree::from_space(space, 2, 4).with_split_criterion(10, 1e-3);
+
+        assert_eq!(tree.dim(), 2);
+        assert_eq!(tree.max_leaves(), 4);
+
+        // The limits are taken from the space, so the split is made at the
+        // midpoint of the second dimension:
+        for i in 0..10 {
+            let y = if i % 2 == 0 { 2.5 } else { 7.5 };
+
+            tree.update(&vec![0.0, y], vec![if y < 5.0 { 1.0 } else { -1.0 }, 0.0]);
+        }
+
+        assert_eq!(tree.n_leaves(), 2);
+        assert_eq!(tree.leaf_index(&[-0.9, 4.9]), tree.leaf_index(&[0.9, 0.1]));
+        assert_ne!(tree.leaf_index(&[0.0, 4.9]), tree.leaf_index(&[0.0, 5.1]));
+    }
+
+    #[test]
+    fn test_split_on_relevant_dimension() {
+        let mut tree = RegressionTree::new(vec![(0.0, 1.0), (0.0, 1.0)], 1, 8)
+            .with_split_criterion(20, 1e-2);
+
+        // The target depends only on the second dimension:
+        for i in 0..40 {
+            let x = (i % 10) as f64 / 10.0 + 0.05;
+            let y = if i % 2 == 0 { 0.25 } else { 0.75 };
+            let error = if y < 0.5 { 1.0 } else { -1.0 };
+
+            tree.update(&vec![x, y], error);
+
+            if tree.n_leaves() > 1 {
+                break;
+            }
+        }
+
+        assert_eq!(tree.n_leaves(), 2);
+        assert_eq!(tree.leaf_index(&[0.1, 0.25]), tree.leaf_index(&[0.9, 0.25]));
+        assert_ne!(tree.leaf_index(&[0.1, 0.25]), tree.leaf_index(&[0.1, 0.75]));
+    }
+
+    #[test]
+    fn test_regression() {
+        let mut tree = RegressionTree::new(vec![(0.0, 1.0), (0.0, 1.0)], 1, 16)
+            .with_split_criterion(50, 1e-4);
+
+        let target = |x: f64, y: f64| if x < 0.5 && y < 0.5 { 1.0 } else { 0.0 };
+
+        // Sweep the grid in an interleaved order, such that the errors seen by
+        // each leaf are not dominated by a single region:
+        for i in 0..20000 {
+            let x = (37 * i % 100) as f64 / 100.0 + 0.005;
+            let y = (59 * i % 101) as f64 / 101.0 + 0.005;
+            let error = target(x, y) - value(&tree, x, y);
+
+            tree.update(&vec![x, y], 0.1 * error);
+        }
+
+        assert!(tree.n_leaves() >= 3);
+        assert!((value(&tree, 0.25, 0.25) - 1.0).abs() < 0.1);
+        assert!(value(&tree, 0.75, 0.25).abs() < 0.1);
+        assert!(value(&tree, 0.25, 0.75).abs() < 0.1);
+        assert!(value(&tree, 0.75, 0.75).abs() < 0.1);
+    }
+}
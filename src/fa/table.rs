@@ -1,11 +1,44 @@
-use super::{Function, Parameterised};
+use super::{Function, Parameterised, VFunction, QFunction};
+use geometry::{Space, FiniteSpace};
+use ndarray::{Array1, Array2};
+use serde::{Serialize, Deserialize};
 use std::collections::HashMap;
 use std::hash::Hash;
 
-use std::ops::AddAssign;
+
+/// Values that can be incremented in place by an error of the same type.
+pub trait Accumulate {
+    fn accumulate(&mut self, error: Self);
+}
+
+macro_rules! impl_accumulate {
+    ($($t:ty),*) => {$(
+        impl Accumulate for $t {
+            fn accumulate(&mut self, error: $t) {
+                *self += error;
+            }
+        }
+    )*}
+}
+
+impl_accumulate!(f32, f64, i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+impl<T: Accumulate> Accumulate for Vec<T> {
+    fn accumulate(&mut self, error: Vec<T>) {
+        for (v, e) in self.iter_mut().zip(error.into_iter()) {
+            v.accumulate(e);
+        }
+    }
+}
+
 
 /// Generic tabular function representation.
 ///
+/// Entries are created on demand; inputs that have never been updated
+/// evaluate to the table's default value. Setting the default above the
+/// expected returns yields optimistic initialisation. A table storing a
+/// `Vec<f64>` per state acts as a `QFunction` with one entry per action.
+///
 /// # Examples
 ///
 /// Basic usage:
@@ -22,38 +55,252 @@ use std::ops::AddAssign;
 /// };
 ///
 /// assert_eq!(f.evaluate(&(0, 1)), 1.0);
+/// assert_eq!(f.evaluate(&(1, 0)), 0.0);
+/// assert_eq!(f.visits(&(0, 1)), 1);
+/// ```
+///
+/// Optimistic action-values:
+///
+/// ```
+/// use rsrl::fa::{Function, QFunction};
+/// use rsrl::fa::Table;
+/// use rsrl::geometry::PairSpace;
+/// use rsrl::geometry::dimensions::Discrete;
+///
+/// let mut q = Table::<(usize, usize), Vec<f64>>::with_default(vec![10.0; 4]);
+/// QFunction::<PairSpace<Discrete, Discrete>>::update_action(&mut q, &(2, 3), 1, -5.0);
+///
+/// let qs: Vec<f64> = q.evaluate(&(2, 3));
+/// assert_eq!(qs, vec![10.0, 5.0, 10.0, 10.0]);
 /// ```
 #[derive(Clone, Serialize, Deserialize)]
-pub struct Table<K: Hash + Eq, V>(pub HashMap<K, V>);
+#[serde(bound(serialize = "K: Serialize, V: Serialize",
+              deserialize = "K: Deserialize<'de>, V: Deserialize<'de>"))]
+pub struct Table<K: Hash + Eq, V> {
+    #[serde(with = "::utils::map_as_pairs")]
+    pub values: HashMap<K, V>,
+    pub default: V,
 
-impl<K: Hash + Eq, V> Table<K, V> {
+    #[serde(with = "::utils::map_as_pairs")]
+    visits: HashMap<K, usize>,
+}
+
+impl<K: Hash + Eq, V: Default> Table<K, V> {
     pub fn new() -> Self {
-        Table(HashMap::new())
+        Table::with_default(V::default())
+    }
+}
+
+impl<K: Hash + Eq, V> Table<K, V> {
+    pub fn with_default(default: V) -> Self {
+        Table {
+            values: HashMap::new(),
+            default: default,
+
+            visits: HashMap::new(),
+        }
+    }
+
+    /// Returns the number of updates applied to the entry for `input`.
+    pub fn visits(&self, input: &K) -> usize {
+        self.visits.get(input).cloned().unwrap_or(0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> Table<K, V> {
+    fn entry(&mut self, input: &K) -> &mut V {
+        *self.visits.entry(input.clone()).or_insert(0) += 1;
+
+        let default = &self.default;
+        self.values.entry(input.clone()).or_insert_with(|| default.clone())
     }
 }
 
-// TODO: Have to deal with attempts to evaluate when no value is present.
-//       Really we need a map with defaults.
-//       The issue arises when we try to consider what the default value may be
-//       for the generic type O.
 impl<I, O> Function<I, O> for Table<I, O>
     where I: Hash + Eq,
-          O: Copy + Default
+          O: Clone
 {
     fn evaluate(&self, input: &I) -> O {
-        if self.0.contains_key(input) {
-            self.0[input]
-        } else {
-            O::default()
-        }
+        self.values.get(input).unwrap_or(&self.default).clone()
     }
 }
 
 impl<I, E> Parameterised<I, E> for Table<I, E>
-    where I: Hash + Eq + Copy,
-          E: Clone + Default + AddAssign
+    where I: Hash + Eq + Clone,
+          E: Clone + Accumulate
 {
     fn update(&mut self, input: &I, error: E) {
-        *self.0.entry(*input).or_insert(E::default()) += error;
+        self.entry(input).accumulate(error);
+    }
+}
+
+impl<S: Space> VFunction<S> for Table<S::Repr, f64>
+    where S::Repr: Hash + Eq
+{}
+
+impl<S: Space> QFunction<S> for Table<S::Repr, Vec<f64>>
+    where S::Repr: Hash + Eq
+{
+    fn evaluate_action(&self, input: &S::Repr, action: usize) -> f64 {
+        self.values.get(input).unwrap_or(&self.default)[action]
+    }
+
+    fn update_action(&mut self, input: &S::Repr, action: usize, error: f64) {
+        self.entry(input)[action] += error;
+    }
+}
+
+
+/// Tabular function representation backed by a dense array over a finite
+/// state space.
+///
+/// Each state is mapped to a row via `FiniteSpace::index`, with one column per
+/// output. Unlike `Table` all entries are allocated up front, trading memory
+/// for constant time lookups.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DenseTable<S: FiniteSpace> {
+    pub space: S,
+    pub values: Array2<f64>,
+
+    visits: Array1<usize>,
+}
+
+impl<S: FiniteSpace> DenseTable<S> {
+    pub fn new(space: S, n_outputs: usize) -> Self {
+        DenseTable::with_default(space, n_outputs, 0.0)
+    }
+
+    pub fn with_default(space: S, n_outputs: usize, default: f64) -> Self {
+        let n_states = space.n_elements();
+
+        DenseTable {
+            space: space,
+            values: Array2::from_elem((n_states, n_outputs), default),
+
+            visits: Array1::zeros((n_states,)),
+        }
+    }
+
+    /// Returns the number of updates applied to the entry for `input`.
+    pub fn visits(&self, input: &S::Repr) -> usize {
+        self.visits[self.space.index(input)]
+    }
+
+    fn row(&mut self, input: &S::Repr) -> usize {
+        let i = self.space.index(input);
+        self.visits[i] += 1;
+
+        i
+    }
+}
+
+impl<S: FiniteSpace> Function<S::Repr, f64> for DenseTable<S> {
+    fn evaluate(&self, input: &S::Repr) -> f64 {
+        self.values[(self.space.index(input), 0)]
+    }
+}
+
+impl<S: FiniteSpace> Function<S::Repr, Vec<f64>> for DenseTable<S> {
+    fn evaluate(&self, input: &S::Repr) -> Vec<f64> {
+        self.values.row(self.space.index(input)).to_vec()
+    }
+}
+
+impl<S: FiniteSpace> Parameterised<S::Repr, f64> for DenseTable<S> {
+    fn update(&mut self, input: &S::Repr, error: f64) {
+        let i = self.row(input);
+
+        self.values[(i, 0)] += error;
+    }
+}
+
+impl<S: FiniteSpace> Parameterised<S::Repr, Vec<f64>> for DenseTable<S> {
+    fn update(&mut self, input: &S::Repr, errors: Vec<f64>) {
+        let i = self.row(input);
+
+        for (v, e) in self.values.row_mut(i).iter_mut().zip(errors.into_iter()) {
+            *v += e;
+        }
+    }
+}
+
+impl<S: FiniteSpace> VFunction<S> for DenseTable<S> {}
+
+impl<S: FiniteSpace> QFunction<S> for DenseTable<S> {
+    fn evaluate_action(&self, input: &S::Repr, action: usize) -> f64 {
+        self.values[(self.space.index(input), action)]
+    }
+
+    fn update_action(&mut self, input: &S::Repr, action: usize, error: f64) {
+        let i = self.row(input);
+
+        self.values[(i, action)] += error;
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{Table, DenseTable};
+    use fa::{Function, Parameterised, QFunction};
+    use geometry::PairSpace;
+    use geometry::dimensions::Discrete;
+
+    type Grid = PairSpace<Discrete, Discrete>;
+
+    fn values<F: Function<(usize, usize), Vec<f64>>>(f: &F, input: &(usize, usize)) -> Vec<f64> {
+        f.evaluate(input)
+    }
+
+    #[test]
+    fn test_default() {
+        let mut t = Table::<u32, f64>::with_default(5.0);
+
+        assert_eq!(t.evaluate(&3), 5.0);
+        assert!(t.is_empty());
+
+        t.update(&3, -1.0);
+
+        assert_eq!(t.evaluate(&3), 4.0);
+        assert_eq!(t.evaluate(&4), 5.0);
+        assert_eq!(t.len(), 1);
+    }
+
+    #[test]
+    fn test_q_table() {
+        let mut t = Table::<(usize, usize), Vec<f64>>::with_default(vec![1.0; 3]);
+
+        QFunction::<Grid>::update_action(&mut t, &(0, 1), 2, 0.5);
+        t.update(&(0, 1), vec![1.0, 0.0, 1.0]);
+
+        assert_eq!(values(&t, &(0, 1)), vec![2.0, 1.0, 2.5]);
+        assert_eq!(values(&t, &(1, 1)), vec![1.0; 3]);
+        assert_eq!(QFunction::<Grid>::evaluate_action(&t, &(0, 1), 2), 2.5);
+
+        assert_eq!(t.visits(&(0, 1)), 2);
+        assert_eq!(t.visits(&(1, 0)), 0);
+    }
+
+    #[test]
+    fn test_dense_table() {
+        let space = PairSpace::new(Discrete::new(3), Discrete::new(2));
+        let mut t = DenseTable::with_default(space, 2, 1.0);
+
+        assert_eq!(t.values.shape(), &[6, 2]);
+
+        t.update_action(&(2, 1), 1, 3.0);
+        t.update(&(2, 1), vec![-1.0, 1.0]);
+
+        assert_eq!(values(&t, &(2, 1)), vec![0.0, 5.0]);
+        assert_eq!(values(&t, &(0, 0)), vec![1.0, 1.0]);
+        assert_eq!(t.visits(&(2, 1)), 2);
     }
 }
@@ -0,0 +1,392 @@
+use ndarray::{Axis, Array1, Array2};
+use std::ops::{Add, Mul};
+
+
+/// Positive semi-definite kernel function.
+pub trait Kernel {
+    /// Evaluates the kernel for a pair of inputs.
+    fn kernel(&self, x: &[f64], y: &[f64]) -> f64;
+
+    /// Evaluates the kernel between each row of `xs` and the input `y`.
+    fn kernel_vector(&self, xs: &Array2<f64>, y: &[f64]) -> Array1<f64> {
+        Array1::from_iter(xs.axis_iter(Axis(0)).map(|x| self.kernel(&x.to_vec(), y)))
+    }
+
+    /// Evaluates the Gram matrix between the rows of `xs` and the rows of `ys`.
+    fn kernel_matrix(&self, xs: &Array2<f64>, ys: &Array2<f64>) -> Array2<f64> {
+        let xs_vec: Vec<Vec<f64>> = xs.axis_iter(Axis(0)).map(|x| x.to_vec()).collect();
+        let ys_vec: Vec<Vec<f64>> = ys.axis_iter(Axis(0)).map(|y| y.to_vec()).collect();
+
+        Array2::from_shape_fn((xs.rows(), ys.rows()),
+                              |(i, j)| self.kernel(&xs_vec[i], &ys_vec[j]))
+    }
+}
+
+impl<K: Kernel + ?Sized> Kernel for Box<K> {
+    fn kernel(&self, x: &[f64], y: &[f64]) -> f64 {
+        (**self).kernel(x, y)
+    }
+}
+
+
+fn sq_distance(x: &[f64], y: &[f64]) -> f64 {
+    x.iter().zip(y.iter()).fold(0.0, |acc, (a, b)| acc + (a - b) * (a - b))
+}
+
+fn inner_product(x: &[f64], y: &[f64]) -> f64 {
+    x.iter().zip(y.iter()).fold(0.0, |acc, (a, b)| acc + a * b)
+}
+
+
+/// Gaussian (squared exponential) kernel: a·exp(-||x - y||² / 2l²).
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Gaussian {
+    pub lengthscale: f64,
+    pub amplitude: f64,
+}
+
+pub type RBF = Gaussian;
+
+impl Gaussian {
+    pub fn new(lengthscale: f64, amplitude: f64) -> Self {
+        Gaussian {
+            lengthscale: lengthscale,
+            amplitude: amplitude,
+        }
+    }
+}
+
+impl Default for Gaussian {
+    fn default() -> Gaussian {
+        Gaussian::new(1.0, 1.0)
+    }
+}
+
+impl Kernel for Gaussian {
+    fn kernel(&self, x: &[f64], y: &[f64]) -> f64 {
+        let r2 = sq_distance(x, y) / (self.lengthscale * self.lengthscale);
+
+        self.amplitude * (-0.5 * r2).exp()
+    }
+}
+
+
+/// Exponential kernel: a·exp(-||x - y|| / l).
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Exponential {
+    pub lengthscale: f64,
+    pub amplitude: f64,
+}
+
+impl Exponential {
+    pub fn new(lengthscale: f64, amplitude: f64) -> Self {
+        Exponential {
+            lengthscale: lengthscale,
+            amplitude: amplitude,
+        }
+    }
+}
+
+impl Default for Exponential {
+    fn default() -> Exponential {
+        Exponential::new(1.0, 1.0)
+    }
+}
+
+impl Kernel for Exponential {
+    fn kernel(&self, x: &[f64], y: &[f64]) -> f64 {
+        let r = sq_distance(x, y).sqrt() / self.lengthscale;
+
+        self.amplitude * (-r).exp()
+    }
+}
+
+
+/// Matérn kernel with ν = 3/2: a·(1 + √3r/l)·exp(-√3r/l).
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Matern32 {
+    pub lengthscale: f64,
+    pub amplitude: f64,
+}
+
+impl Matern32 {
+    pub fn new(lengthscale: f64, amplitude: f64) -> Self {
+        Matern32 {
+            lengthscale: lengthscale,
+            amplitude: amplitude,
+        }
+    }
+}
+
+impl Kernel for Matern32 {
+    fn kernel(&self, x: &[f64], y: &[f64]) -> f64 {
+        let r = 3.0f64.sqrt() * sq_distance(x, y).sqrt() / self.lengthscale;
+
+        self.amplitude * (1.0 + r) * (-r).exp()
+    }
+}
+
+
+/// Matérn kernel with ν = 5/2: a·(1 + √5r/l + 5r²/3l²)·exp(-√5r/l).
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Matern52 {
+    pub lengthscale: f64,
+    pub amplitude: f64,
+}
+
+impl Matern52 {
+    pub fn new(lengthscale: f64, amplitude: f64) -> Self {
+        Matern52 {
+            lengthscale: lengthscale,
+            amplitude: amplitude,
+        }
+    }
+}
+
+impl Kernel for Matern52 {
+    fn kernel(&self, x: &[f64], y: &[f64]) -> f64 {
+        let r = 5.0f64.sqrt() * sq_distance(x, y).sqrt() / self.lengthscale;
+
+        self.amplitude * (1.0 + r + r * r / 3.0) * (-r).exp()
+    }
+}
+
+
+/// Polynomial kernel: (x·y + c)^d.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Polynomial {
+    pub degree: i32,
+    pub offset: f64,
+}
+
+impl Polynomial {
+    pub fn new(degree: i32, offset: f64) -> Self {
+        Polynomial {
+            degree: degree,
+            offset: offset,
+        }
+    }
+}
+
+impl Kernel for Polynomial {
+    fn kernel(&self, x: &[f64], y: &[f64]) -> f64 {
+        (inner_product(x, y) + self.offset).powi(self.degree)
+    }
+}
+
+
+/// Linear kernel: x·y + c.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Linear {
+    pub offset: f64,
+}
+
+impl Linear {
+    pub fn new(offset: f64) -> Self {
+        Linear { offset: offset }
+    }
+}
+
+impl Default for Linear {
+    fn default() -> Linear {
+        Linear::new(0.0)
+    }
+}
+
+impl Kernel for Linear {
+    fn kernel(&self, x: &[f64], y: &[f64]) -> f64 {
+        inner_product(x, y) + self.offset
+    }
+}
+
+
+/// Epanechnikov kernel: max(0, 3/4·(1 - ||x - y||² / h²)).
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Epanechnikov {
+    pub bandwidth: f64,
+}
+
+impl Epanechnikov {
+    pub fn new(bandwidth: f64) -> Self {
+        Epanechnikov { bandwidth: bandwidth }
+    }
+}
+
+impl Kernel for Epanechnikov {
+    fn kernel(&self, x: &[f64], y: &[f64]) -> f64 {
+        let u2 = sq_distance(x, y) / (self.bandwidth * self.bandwidth);
+
+        if u2 < 1.0 { 0.75 * (1.0 - u2) } else { 0.0 }
+    }
+}
+
+
+/// Sum of two kernels.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SumKernel<K1: Kernel, K2: Kernel>(pub K1, pub K2);
+
+impl<K1: Kernel, K2: Kernel> SumKernel<K1, K2> {
+    pub fn new(k1: K1, k2: K2) -> Self {
+        SumKernel(k1, k2)
+    }
+}
+
+impl<K1: Kernel, K2: Kernel> Kernel for SumKernel<K1, K2> {
+    fn kernel(&self, x: &[f64], y: &[f64]) -> f64 {
+        self.0.kernel(x, y) + self.1.kernel(x, y)
+    }
+}
+
+
+/// Product of two kernels.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ProductKernel<K1: Kernel, K2: Kernel>(pub K1, pub K2);
+
+impl<K1: Kernel, K2: Kernel> ProductKernel<K1, K2> {
+    pub fn new(k1: K1, k2: K2) -> Self {
+        ProductKernel(k1, k2)
+    }
+}
+
+impl<K1: Kernel, K2: Kernel> Kernel for ProductKernel<K1, K2> {
+    fn kernel(&self, x: &[f64], y: &[f64]) -> f64 {
+        self.0.kernel(x, y) * self.1.kernel(x, y)
+    }
+}
+
+
+macro_rules! impl_kernel_ops {
+    ($name: ident) => {
+        impl<K: Kernel> Add<K> for $name {
+            type Output = SumKernel<$name, K>;
+
+            fn add(self, other: K) -> SumKernel<$name, K> {
+                SumKernel(self, other)
+            }
+        }
+
+        impl<K: Kernel> Mul<K> for $name {
+            type Output = ProductKernel<$name, K>;
+
+            fn mul(self, other: K) -> ProductKernel<$name, K> {
+                ProductKernel(self, other)
+            }
+        }
+    };
+    ($name: ident<$($t: ident),+>) => {
+        impl<$($t: Kernel),+, K: Kernel> Add<K> for $name<$($t),+> {
+            type Output = SumKernel<$name<$($t),+>, K>;
+
+            fn add(self, other: K) -> SumKernel<$name<$($t),+>, K> {
+                SumKernel(self, other)
+            }
+        }
+
+        impl<$($t: Kernel),+, K: Kernel> Mul<K> for $name<$($t),+> {
+            type Output = ProductKernel<$name<$($t),+>, K>;
+
+            fn mul(self, other: K) -> ProductKernel<$name<$($t),+>, K> {
+                ProductKernel(self, other)
+            }
+        }
+    }
+}
+
+impl_kernel_ops!(Gaussian);
+impl_kernel_ops!(Exponential);
+impl_kernel_ops!(Matern32);
+impl_kernel_ops!(Matern52);
+impl_kernel_ops!(Polynomial);
+impl_kernel_ops!(Linear);
+impl_kernel_ops!(Epanechnikov);
+impl_kernel_ops!(SumKernel<K1, K2>);
+impl_kernel_ops!(ProductKernel<K1, K2>);
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::arr2;
+    use serde_json;
+
+    #[test]
+    fn test_gaussian() {
+        let k = Gaussian::new(1.0, 2.0);
+
+        assert_eq!(k.kernel(&[0.0], &[0.0]), 2.0);
+        assert!((k.kernel(&[0.0], &[1.0]) - 2.0 * (-0.5f64).exp()).abs() < 1e-7);
+        assert_eq!(k.kernel(&[0.0, 1.0], &[1.0, 0.0]), k.kernel(&[1.0, 0.0], &[0.0, 1.0]));
+    }
+
+    #[test]
+    fn test_stationary_decay() {
+        let ks: Vec<Box<Kernel>> = vec![
+            Box::new(Gaussian::new(1.0, 1.0)),
+            Box::new(Exponential::new(1.0, 1.0)),
+            Box::new(Matern32::new(1.0, 1.0)),
+            Box::new(Matern52::new(1.0, 1.0)),
+            Box::new(Epanechnikov::new(5.0)),
+        ];
+
+        for k in ks.iter() {
+            let mut prev = k.kernel(&[0.0], &[0.0]);
+
+            for i in 1..10 {
+                let v = k.kernel(&[0.0], &[i as f64 / 2.0]);
+                assert!(v < prev);
+
+                prev = v;
+            }
+        }
+    }
+
+    #[test]
+    fn test_epanechnikov_support() {
+        let k = Epanechnikov::new(1.0);
+
+        assert_eq!(k.kernel(&[0.0], &[0.0]), 0.75);
+        assert_eq!(k.kernel(&[0.0], &[1.0]), 0.0);
+        assert_eq!(k.kernel(&[0.0], &[2.0]), 0.0);
+    }
+
+    #[test]
+    fn test_dot_product_kernels() {
+        assert_eq!(Linear::new(1.0).kernel(&[1.0, 2.0], &[3.0, 4.0]), 12.0);
+        assert_eq!(Polynomial::new(2, 1.0).kernel(&[1.0, 2.0], &[3.0, 4.0]), 144.0);
+    }
+
+    #[test]
+    fn test_composition() {
+        let k = Gaussian::new(1.0, 1.0) + Linear::new(0.0) * Linear::new(1.0);
+        let (x, y) = ([1.0, 0.5], [0.0, 2.0]);
+
+        let expected = Gaussian::new(1.0, 1.0).kernel(&x, &y) +
+                       Linear::new(0.0).kernel(&x, &y) * Linear::new(1.0).kernel(&x, &y);
+
+        assert!((k.kernel(&x, &y) - expected).abs() < 1e-7);
+    }
+
+    #[test]
+    fn test_kernel_matrix() {
+        let k = Gaussian::default();
+        let xs = arr2(&[[0.0, 0.0], [1.0, 0.0], [0.0, 1.0]]);
+        let ys = arr2(&[[0.0, 0.0], [1.0, 1.0]]);
+
+        let gram = k.kernel_matrix(&xs, &ys);
+
+        assert_eq!(gram.shape(), &[3, 2]);
+        assert_eq!(gram[(0, 0)], 1.0);
+        assert_eq!(gram[(1, 1)], gram[(2, 1)]);
+        assert_eq!(k.kernel_vector(&xs, &[1.0, 1.0]), gram.column(1));
+    }
+
+    #[test]
+    fn test_serialisation() {
+        let k = Gaussian::new(0.5, 2.0) * Matern52::new(1.0, 1.0);
+        let json = serde_json::to_string(&k).unwrap();
+
+        let k2: ProductKernel<Gaussian, Matern52> = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(k, k2);
+    }
+}
@@ -15,6 +15,16 @@ pub trait Space {
     fn span(&self) -> Span;
 }
 
+/// A space with a finite number of elements that can be enumerated.
+pub trait FiniteSpace: Space {
+    /// Maps an element of the space onto a unique index in `0..n_elements()`.
+    fn index(&self, value: &Self::Repr) -> usize;
+
+    fn n_elements(&self) -> usize {
+        self.span().into()
+    }
+}
+
 pub type ActionSpace = UnitarySpace<dimensions::Discrete>;
 
 
@@ -63,6 +73,12 @@ impl<D: Dimension> Space for UnitarySpace<D> {
     }
 }
 
+impl FiniteSpace for UnitarySpace<dimensions::Discrete> {
+    fn index(&self, value: &usize) -> usize {
+        *value
+    }
+}
+
 
 #[derive(Clone, Copy, Serialize, Deserialize, Debug)]
 pub struct PairSpace<D1, D2>((D1, D2))
@@ -91,6 +107,14 @@ impl<D1: Dimension, D2: Dimension> Space for PairSpace<D1, D2> {
     }
 }
 
+impl FiniteSpace for PairSpace<dimensions::Discrete, dimensions::Discrete> {
+    fn index(&self, value: &(usize, usize)) -> usize {
+        let n1: usize = (self.0).0.span().into();
+
+        value.0 + n1 * value.1
+    }
+}
+
 impl PairSpace<dimensions::Continuous, dimensions::Continuous> {
     pub fn partitioned(self, density: usize) -> PairSpace<Partitioned, Partitioned> {
         PairSpace((Partitioned::from_continuous((self.0).0, density),
@@ -157,6 +181,16 @@ impl RegularSpace<dimensions::Partitioned> {
     }
 }
 
+impl FiniteSpace for RegularSpace<dimensions::Discrete> {
+    fn index(&self, value: &Vec<usize>) -> usize {
+        self.dimensions.iter().zip(value.iter()).rev().fold(0, |acc, (d, v)| {
+            let n: usize = d.span().into();
+
+            v + n * acc
+        })
+    }
+}
+
 impl<D: Dimension> FromIterator<D> for RegularSpace<D> {
     fn from_iter<I: IntoIterator<Item = D>>(iter: I) -> Self {
         let mut s = Self::new();
@@ -222,7 +256,7 @@ impl<D: Dimension> IntoIterator for RegularSpace<D> {
 
 #[cfg(test)]
 mod tests {
-    use super::{Space, NullSpace, UnitarySpace, PairSpace, RegularSpace};
+    use super::{Space, FiniteSpace, NullSpace, UnitarySpace, PairSpace, RegularSpace};
     use geometry::Span;
     use geometry::dimensions::*;
     use ndarray::arr1;
@@ -283,4 +317,24 @@ mod tests {
         assert_eq!(ps.dim(), 2);
         assert_eq!(ps.span(), d1.span()*d2.span());
     }
+
+    #[test]
+    fn test_finite_index() {
+        let ps = PairSpace::new(Discrete::new(3), Discrete::new(2));
+
+        assert_eq!(ps.n_elements(), 6);
+        assert_eq!(ps.index(&(0, 0)), 0);
+        assert_eq!(ps.index(&(2, 0)), 2);
+        assert_eq!(ps.index(&(1, 1)), 4);
+
+        let rs = RegularSpace::new()
+            .push(Discrete::new(3))
+            .push(Discrete::new(2))
+            .push(Discrete::new(4));
+
+        assert_eq!(rs.n_elements(), 24);
+        assert_eq!(rs.index(&vec![0, 0, 0]), 0);
+        assert_eq!(rs.index(&vec![2, 1, 3]), 23);
+        assert_eq!(rs.index(&vec![1, 1, 0]), 4);
+    }
 }
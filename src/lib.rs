@@ -9,6 +9,7 @@ extern crate ndarray;
 extern crate serde;
 extern crate serde_json;
 extern crate serde_test;
+extern crate bincode;
 #[macro_use] extern crate serde_derive;
 
 // extern crate futures;
@@ -22,12 +23,13 @@ mod consts;
 mod macros;
 
 pub mod parameter;
-pub use self::parameter::Parameter;
+pub use self::parameter::{Parameter, Clock};
 
 mod experiment;
 pub use self::experiment::*;
 
 pub mod agents;
+pub mod checkpoint;
 pub mod domains;
 pub mod fa;
 pub mod geometry;
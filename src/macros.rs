@@ -26,3 +26,9 @@ macro_rules! clip {
         }
     }
 }
+
+macro_rules! tick {
+    ($clock:expr => $($p:expr),+) => {
+        $( $p = $p.tick($clock); )+
+    }
+}
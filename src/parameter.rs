@@ -3,7 +3,30 @@ use std::f64;
 use std::ops::{Add, Sub, Mul, Div};
 
 
-#[derive(Clone, Copy)]
+/// The event on which a `Parameter` schedule advances.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Clock {
+    /// Every transition observed by an agent.
+    Step,
+
+    /// Every learning update applied by an agent; for batch learners such as
+    /// DQN this differs from the number of transitions.
+    Update,
+
+    /// Every terminal state, i.e. once per episode.
+    Episode,
+}
+
+
+/// A scalar (hyper)parameter that may follow a schedule over time.
+///
+/// Schedules are advanced with `step` and rewound with `back`; the counter may
+/// represent time steps, episodes or, for tabular learners, visit counts.
+///
+/// Agents and policies advance their parameters with `tick`, which only steps
+/// a schedule when the event matches its `Clock`. Parameters run on
+/// `Clock::Episode` unless declared otherwise via `with_clock`.
+#[derive(Clone, Serialize, Deserialize)]
 pub enum Parameter {
     Fixed(f64),
     Exponential {
@@ -27,7 +50,70 @@ pub enum Parameter {
         tau: f64,
 
         count: u32
-    }
+    },
+
+    /// Linear interpolation from `init` to `end` over `n_steps`, after which
+    /// the value is held constant.
+    Linear {
+        init: f64,
+        end: f64,
+        n_steps: u32,
+
+        count: u32,
+    },
+
+    /// Piecewise-constant schedule taking `values[i]` once `i` of the
+    /// `boundaries` have been passed.
+    Piecewise {
+        values: Vec<f64>,
+        boundaries: Vec<u32>,
+
+        count: u32,
+    },
+
+    /// Cosine annealing from `init` to `floor` with warm restarts. The first
+    /// cycle lasts `period` steps and each subsequent one `mult` times longer.
+    ///
+    /// Loshchilov, Ilya, and Frank Hutter. "SGDR: Stochastic gradient descent
+    /// with warm restarts." arXiv preprint arXiv:1608.03983 (2016).
+    Cosine {
+        init: f64,
+        floor: f64,
+        period: u32,
+        mult: f64,
+
+        count: u32,
+    },
+
+    /// Triangular cycling between `lower` and `upper`, taking `half_period`
+    /// steps in each direction.
+    ///
+    /// Smith, Leslie N. "Cyclical learning rates for training neural
+    /// networks." Applications of Computer Vision (WACV), 2017 IEEE Winter
+    /// Conference on. IEEE, 2017.
+    Cyclical {
+        lower: f64,
+        upper: f64,
+        half_period: u32,
+
+        count: u32,
+    },
+
+    /// Harmonic decay `init * offset / (offset + count)`; with `offset = 1`
+    /// and `count` the number of visits this is the classical `1/n` rule.
+    Harmonic {
+        init: f64,
+        offset: f64,
+
+        count: u32,
+    },
+
+    Min(Box<Parameter>, Box<Parameter>),
+    Max(Box<Parameter>, Box<Parameter>),
+    Product(Box<Parameter>, Box<Parameter>),
+
+    /// A schedule advanced on a specific `Clock`.
+    Clocked(Clock, Box<Parameter>),
 }
 
 impl Parameter {
@@ -65,6 +151,101 @@ impl Parameter {
         }
     }
 
+    pub fn linear(init: f64, end: f64, n_steps: u32) -> Parameter {
+        Parameter::Linear {
+            init: init,
+            end: end,
+            n_steps: n_steps,
+
+            count: 0,
+        }
+    }
+
+    pub fn piecewise(values: Vec<f64>, boundaries: Vec<u32>) -> Parameter {
+        if values.len() != boundaries.len() + 1 {
+            panic!("A piecewise schedule needs exactly one more value ({}) than boundaries ({}).",
+                   values.len(),
+                   boundaries.len());
+        }
+
+        Parameter::Piecewise {
+            values: values,
+            boundaries: boundaries,
+
+            count: 0,
+        }
+    }
+
+    pub fn cosine(init: f64, floor: f64, period: u32, mult: f64) -> Parameter {
+        if period == 0 || mult < 1.0 {
+            panic!("Cosine schedules require a positive period and mult >= 1.");
+        }
+
+        Parameter::Cosine {
+            init: init,
+            floor: floor,
+            period: period,
+            mult: mult,
+
+            count: 0,
+        }
+    }
+
+    pub fn cyclical(lower: f64, upper: f64, half_period: u32) -> Parameter {
+        if half_period == 0 {
+            panic!("Cyclical schedules require a positive half period.");
+        }
+
+        Parameter::Cyclical {
+            lower: lower,
+            upper: upper,
+            half_period: half_period,
+
+            count: 0,
+        }
+    }
+
+    pub fn harmonic(init: f64, offset: f64) -> Parameter {
+        if offset <= 0.0 {
+            panic!("Harmonic schedules require a positive offset.");
+        }
+
+        Parameter::Harmonic {
+            init: init,
+            offset: offset,
+
+            count: 0,
+        }
+    }
+
+    pub fn min<T1: Into<Parameter>, T2: Into<Parameter>>(a: T1, b: T2) -> Parameter {
+        Parameter::Min(Box::new(a.into()), Box::new(b.into()))
+    }
+
+    pub fn max<T1: Into<Parameter>, T2: Into<Parameter>>(a: T1, b: T2) -> Parameter {
+        Parameter::Max(Box::new(a.into()), Box::new(b.into()))
+    }
+
+    pub fn product<T1: Into<Parameter>, T2: Into<Parameter>>(a: T1, b: T2) -> Parameter {
+        Parameter::Product(Box::new(a.into()), Box::new(b.into()))
+    }
+
+    /// Declares the event on which this schedule advances. For combinators
+    /// this applies to every operand without a clock of its own.
+    pub fn with_clock(self, clock: Clock) -> Parameter {
+        match self {
+            Parameter::Clocked(_, p) => Parameter::Clocked(clock, p),
+            p => Parameter::Clocked(clock, Box::new(p)),
+        }
+    }
+
+    pub fn clock(&self) -> Clock {
+        match self {
+            &Parameter::Clocked(c, _) => c,
+            _ => Clock::Episode,
+        }
+    }
+
     pub fn value(&self) -> f64 {
         match self {
             &Parameter::Fixed(v) => v,
@@ -80,77 +261,123 @@ impl Parameter {
             &Parameter::GHC { init: i, floor: f, count: c, tau: t } => {
                 f64::max(i*t/(t+c as f64 - 1.0), f)
             },
+
+            &Parameter::Linear { init: i, end: e, n_steps: n, count: c } => {
+                let frac = if n == 0 { 1.0 } else { f64::min(c as f64 / n as f64, 1.0) };
+
+                i + (e - i)*frac
+            },
+
+            &Parameter::Piecewise { ref values, ref boundaries, count: c } => {
+                values[boundaries.iter().filter(|b| c >= **b).count()]
+            },
+
+            &Parameter::Cosine { init: i, floor: f, period, mult, count: c } => {
+                let (t, p) = Parameter::cosine_cycle(c as f64, period as f64, mult);
+
+                f + 0.5*(i - f)*(1.0 + (f64::consts::PI*t/p).cos())
+            },
+
+            &Parameter::Cyclical { lower: l, upper: u, half_period: h, count: c } => {
+                let pos = c % (2*h);
+                let frac = (if pos < h { pos } else { 2*h - pos }) as f64 / h as f64;
+
+                l + (u - l)*frac
+            },
+
+            &Parameter::Harmonic { init: i, offset: o, count: c } => i*o/(o + c as f64),
+
+            &Parameter::Min(ref a, ref b) => f64::min(a.value(), b.value()),
+            &Parameter::Max(ref a, ref b) => f64::max(a.value(), b.value()),
+            &Parameter::Product(ref a, ref b) => a.value()*b.value(),
+
+            &Parameter::Clocked(_, ref p) => p.value(),
+        }
+    }
+
+    /// Returns the position within, and length of, the cycle containing step
+    /// `count`. Cycle lengths are kept real-valued so that they grow even for
+    /// short periods, and the cycle index is found in closed form from the
+    /// geometric series of cycle lengths.
+    fn cosine_cycle(count: f64, period: f64, mult: f64) -> (f64, f64) {
+        if mult == 1.0 {
+            return (count % period, period);
+        }
+
+        // Start of cycle k is period * (mult^k - 1) / (mult - 1):
+        let start = |k: i32| period*(mult.powi(k) - 1.0)/(mult - 1.0);
+        let mut k = (1.0 + count*(mult - 1.0)/period).log(mult).floor() as i32;
+
+        // Guard against rounding at the cycle boundaries:
+        if count < start(k) {
+            k -= 1;
+        } else if count >= start(k + 1) {
+            k += 1;
         }
+
+        (count - start(k), period*mult.powi(k))
     }
 
-    pub fn to_fixed(self) -> Parameter {
+    pub fn to_fixed(&self) -> Parameter {
         Parameter::Fixed(self.value())
     }
 
-    pub fn step(self) -> Parameter {
+    pub fn step(&self) -> Parameter {
+        self.map_count(&|c: u32| c.saturating_add(1))
+    }
+
+    pub fn back(&self) -> Parameter {
+        self.map_count(&|c: u32| c.saturating_sub(1))
+    }
+
+    /// Advances every schedule whose clock matches `event`.
+    pub fn tick(&self, event: Clock) -> Parameter {
+        self.tick_with(event, Clock::Episode)
+    }
+
+    fn tick_with(&self, event: Clock, clock: Clock) -> Parameter {
         match self {
-            Parameter::Fixed(_) => self,
-            Parameter::Exponential { init: i, floor: f, count: c, tau: t } => {
-                Parameter::Exponential {
-                    init: i,
-                    floor: f,
-                    tau: t,
-
-                    count: c.saturating_add(1),
-                }
-            }
-            Parameter::Polynomial { init: i, floor: f, count: c, tau: t } => {
-                Parameter::Polynomial {
-                    init: i,
-                    floor: f,
-                    tau: t,
-
-                    count: c.saturating_add(1),
-                }
-            },
-            Parameter::GHC { init: i, floor: f, count: c, tau: t } => {
-                Parameter::GHC {
-                    init: i,
-                    floor: f,
-                    tau: t,
-
-                    count: c.saturating_add(1),
-                }
-            },
+            &Parameter::Clocked(c, ref p) => Parameter::Clocked(c, Box::new(p.tick_with(event, c))),
+
+            &Parameter::Min(ref a, ref b) =>
+                Parameter::Min(Box::new(a.tick_with(event, clock)), Box::new(b.tick_with(event, clock))),
+            &Parameter::Max(ref a, ref b) =>
+                Parameter::Max(Box::new(a.tick_with(event, clock)), Box::new(b.tick_with(event, clock))),
+            &Parameter::Product(ref a, ref b) =>
+                Parameter::Product(Box::new(a.tick_with(event, clock)),
+                                   Box::new(b.tick_with(event, clock))),
+
+            p if event == clock => p.step(),
+            p => p.clone(),
         }
     }
 
-    pub fn back(self) -> Parameter {
-        match self {
-            Parameter::Fixed(_) => self,
-            Parameter::Exponential { init: i, floor: f, count: c, tau: t } => {
-                Parameter::Exponential {
-                    init: i,
-                    floor: f,
-                    tau: t,
-
-                    count: c.saturating_sub(1),
-                }
-            }
-            Parameter::Polynomial { init: i, floor: f, count: c, tau: t } => {
-                Parameter::Polynomial {
-                    init: i,
-                    floor: f,
-                    tau: t,
-
-                    count: c.saturating_sub(1),
-                }
-            },
-            Parameter::GHC { init: i, floor: f, count: c, tau: t } => {
-                Parameter::GHC {
-                    init: i,
-                    floor: f,
-                    tau: t,
-
-                    count: c.saturating_sub(1),
-                }
+    fn map_count(&self, f: &Fn(u32) -> u32) -> Parameter {
+        let mut p = self.clone();
+
+        match p {
+            Parameter::Fixed(_) => {},
+
+            Parameter::Exponential { ref mut count, .. } |
+            Parameter::Polynomial { ref mut count, .. } |
+            Parameter::GHC { ref mut count, .. } |
+            Parameter::Linear { ref mut count, .. } |
+            Parameter::Piecewise { ref mut count, .. } |
+            Parameter::Cosine { ref mut count, .. } |
+            Parameter::Cyclical { ref mut count, .. } |
+            Parameter::Harmonic { ref mut count, .. } => *count = f(*count),
+
+            Parameter::Min(ref mut a, ref mut b) |
+            Parameter::Max(ref mut a, ref mut b) |
+            Parameter::Product(ref mut a, ref mut b) => {
+                **a = a.map_count(f);
+                **b = b.map_count(f);
             },
+
+            Parameter::Clocked(_, ref mut p) => **p = p.map_count(f),
         }
+
+        p
     }
 }
 
@@ -186,6 +413,30 @@ macro_rules! impl_op {
                 self.value() $op other.value()
             }
         }
+
+        impl<'a> $name<$num_type> for &'a Parameter {
+            type Output = $num_type;
+
+            fn $fn_name(self, other: $num_type) -> $num_type {
+                self.value() $op other
+            }
+        }
+
+        impl<'a> $name<&'a Parameter> for $num_type {
+            type Output = $num_type;
+
+            fn $fn_name(self, other: &'a Parameter) -> $num_type {
+                self $op other.value()
+            }
+        }
+
+        impl<'a, 'b> $name<&'b Parameter> for &'a Parameter {
+            type Output = $num_type;
+
+            fn $fn_name(self, other: &'b Parameter) -> $num_type {
+                self.value() $op other.value()
+            }
+        }
     }
 }
 
@@ -197,7 +448,7 @@ impl_op!(Div, f64, div, /);
 
 #[cfg(test)]
 mod tests {
-    use super::Parameter;
+    use super::{Parameter, Clock};
 
     #[test]
     fn test_fixed() {
@@ -296,4 +547,195 @@ mod tests {
         p = p.step().step().step().back().back();
         assert!((p.value() - 0.9).abs() < 1e-7);
     }
+
+    #[test]
+    fn test_linear() {
+        let mut p = Parameter::linear(1.0, 0.0, 4);
+
+        for v in vec![1.0, 0.75, 0.5, 0.25, 0.0, 0.0] {
+            assert!((p.value() - v).abs() < 1e-7);
+            p = p.step();
+        }
+
+        p = p.back().back().back();
+        assert!((p.value() - 0.25).abs() < 1e-7);
+    }
+
+    #[test]
+    fn test_piecewise() {
+        let mut p = Parameter::piecewise(vec![1.0, 0.1, 0.01], vec![2, 4]);
+
+        for v in vec![1.0, 1.0, 0.1, 0.1, 0.01, 0.01] {
+            assert_eq!(p.value(), v);
+            p = p.step();
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_piecewise_mismatch() {
+        Parameter::piecewise(vec![1.0, 0.1], vec![2, 4]);
+    }
+
+    #[test]
+    fn test_cosine() {
+        let mut p = Parameter::cosine(1.0, 0.0, 4, 2.0);
+
+        // First cycle of length 4:
+        for v in vec![1.0, 0.853553390593274, 0.5, 0.146446609406726] {
+            assert!((p.value() - v).abs() < 1e-7);
+            p = p.step();
+        }
+
+        // Restart with a cycle of length 8:
+        assert!((p.value() - 1.0).abs() < 1e-7);
+
+        for _ in 0..4 {
+            p = p.step();
+        }
+        assert!((p.value() - 0.5).abs() < 1e-7);
+
+        for _ in 0..4 {
+            p = p.step();
+        }
+        assert!((p.value() - 1.0).abs() < 1e-7);
+    }
+
+    #[test]
+    fn test_cosine_short_period() {
+        // Cycles of length 1, 1.5, 2.25, ... must keep growing:
+        let mut p = Parameter::cosine(1.0, 0.0, 1, 1.5);
+
+        for v in vec![1.0, 1.0, 0.25, 0.883022221559489] {
+            assert!((p.value() - v).abs() < 1e-7);
+            p = p.step();
+        }
+    }
+
+    #[test]
+    fn test_cosine_large_count() {
+        let cosine = |period, mult, count| Parameter::Cosine {
+            init: 1.0,
+            floor: 0.0,
+            period: period,
+            mult: mult,
+
+            count: count,
+        };
+
+        assert!((cosine(10, 1.0, 4_000_000_005).value() - 0.5).abs() < 1e-7);
+
+        // Cycle 30 starts at 4 * (2^30 - 1) and lasts 4 * 2^30 steps:
+        assert!((cosine(4, 2.0, 4_294_967_292).value() - 1.0).abs() < 1e-7);
+        assert!((cosine(4, 2.0, 4_294_967_295).value() - 1.0).abs() < 1e-7);
+    }
+
+    #[test]
+    fn test_cyclical() {
+        let mut p = Parameter::cyclical(0.0, 1.0, 2);
+
+        for v in vec![0.0, 0.5, 1.0, 0.5, 0.0, 0.5, 1.0] {
+            assert!((p.value() - v).abs() < 1e-7);
+            p = p.step();
+        }
+    }
+
+    #[test]
+    fn test_harmonic() {
+        let mut p = Parameter::harmonic(1.0, 1.0);
+
+        for n in 1..100 {
+            assert!((p.value() - 1.0 / n as f64).abs() < 1e-7);
+            p = p.step();
+        }
+
+        let q = Parameter::harmonic(0.5, 10.0).step().step();
+        assert!((q.value() - 0.5 * 10.0 / 12.0).abs() < 1e-7);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_harmonic_zero_offset() {
+        Parameter::harmonic(1.0, 0.0);
+    }
+
+    #[test]
+    fn test_combinators() {
+        let decay = Parameter::exponential(1.0, 0.0, 0.5);
+
+        let mut p = Parameter::max(decay.clone(), 0.2);
+        let mut q = Parameter::min(decay.clone(), 0.3);
+        let mut r = Parameter::product(decay, Parameter::linear(1.0, 0.0, 2));
+
+        assert_eq!((p.value(), q.value(), r.value()), (1.0, 0.3, 1.0));
+
+        p = p.step();
+        q = q.step();
+        r = r.step();
+        assert_eq!((p.value(), q.value(), r.value()), (0.5, 0.3, 0.25));
+
+        p = p.step().step();
+        q = q.step().step();
+        r = r.back();
+        assert_eq!((p.value(), q.value(), r.value()), (0.2, 0.125, 1.0));
+    }
+
+    #[test]
+    fn test_ref_ops() {
+        let p = Parameter::fixed(2.0);
+        let q = Parameter::fixed(4.0);
+
+        assert_eq!(&p * 3.0, 6.0);
+        assert_eq!(3.0 - &p, 1.0);
+        assert_eq!(&q / &p, 2.0);
+        assert_eq!(p.clone() + q.clone(), 6.0);
+    }
+
+    #[test]
+    fn test_clock_default() {
+        let p = Parameter::linear(1.0, 0.0, 4);
+
+        assert_eq!(p.clock(), Clock::Episode);
+        assert_eq!(p.tick(Clock::Step).value(), 1.0);
+        assert_eq!(p.tick(Clock::Update).value(), 1.0);
+        assert_eq!(p.tick(Clock::Episode).value(), 0.75);
+    }
+
+    #[test]
+    fn test_clocked() {
+        let mut p = Parameter::linear(1.0, 0.0, 4).with_clock(Clock::Step);
+
+        assert_eq!(p.clock(), Clock::Step);
+
+        p = p.tick(Clock::Episode).tick(Clock::Update);
+        assert_eq!(p.value(), 1.0);
+
+        p = p.tick(Clock::Step).tick(Clock::Step);
+        assert_eq!(p.value(), 0.5);
+
+        p = p.with_clock(Clock::Update).tick(Clock::Update);
+        assert_eq!(p.value(), 0.25);
+
+        p = p.back();
+        assert_eq!(p.value(), 0.5);
+    }
+
+    #[test]
+    fn test_clocked_combinators() {
+        let per_step = Parameter::exponential(1.0, 0.0, 0.5).with_clock(Clock::Step);
+        let per_episode = Parameter::linear(1.0, 0.0, 2);
+
+        let mut p = Parameter::product(per_step, per_episode);
+
+        p = p.tick(Clock::Step);
+        assert_eq!(p.value(), 0.5);
+
+        p = p.tick(Clock::Episode);
+        assert_eq!(p.value(), 0.25);
+
+        let mut q = Parameter::max(Parameter::linear(1.0, 0.0, 4), 0.1).with_clock(Clock::Update);
+
+        q = q.tick(Clock::Episode).tick(Clock::Update);
+        assert_eq!(q.value(), 0.75);
+    }
 }
@@ -1,12 +1,14 @@
-use super::Policy;
+use super::{Policy, FinitePolicy, sample_restricted};
 
-use Parameter;
+use {Parameter, Clock};
 use rand::{Rng, thread_rng, ThreadRng};
 use std::f64;
 
 
+#[derive(Serialize, Deserialize)]
 pub struct Boltzmann {
     tau: Parameter,
+    #[serde(skip, default = "thread_rng")]
     rng: ThreadRng,
 }
 
@@ -30,6 +32,28 @@ impl Policy for Boltzmann {
         }
     }
 
+    fn sample_legal(&mut self, qs: &[f64], actions: &[usize]) -> usize {
+        sample_restricted(self, qs, actions)
+    }
+
+    fn probability(&mut self, qs: &[f64], a: &usize) -> f64 {
+        self.probabilities(qs)[*a]
+    }
+
+    fn handle_transition(&mut self) {
+        tick!(Clock::Step => self.tau);
+    }
+
+    fn handle_update(&mut self) {
+        tick!(Clock::Update => self.tau);
+    }
+
+    fn handle_terminal(&mut self) {
+        tick!(Clock::Episode => self.tau);
+    }
+}
+
+impl FinitePolicy for Boltzmann {
     fn probabilities(&mut self, qs: &[f64]) -> Vec<f64> {
         let mut z = 0.0;
         let ws: Vec<f64> = qs.iter().map(|q| {
@@ -41,16 +65,12 @@ impl Policy for Boltzmann {
 
         ws.iter().map(|w| w / z).collect()
     }
-
-    fn handle_terminal(&mut self) {
-        self.tau = self.tau.step();
-    }
 }
 
 
 #[cfg(test)]
 mod tests {
-    use super::{Policy, Boltzmann, Parameter};
+    use super::{Policy, FinitePolicy, Boltzmann, Parameter};
     use std::f64::consts::E;
     use ndarray::arr1;
 
@@ -93,7 +113,7 @@ mod tests {
     #[test]
     fn test_terminal() {
         let mut tau = Parameter::exponential(100.0, 1.0, 0.9);
-        let mut p = Boltzmann::new(tau);
+        let mut p = Boltzmann::new(tau.clone());
 
         for _ in 0..100 {
             tau = tau.step();
@@ -102,4 +122,20 @@ mod tests {
             assert_eq!(tau.value(), p.tau.value());
         }
     }
+
+    #[test]
+    fn test_legal() {
+        let mut p = Boltzmann::new(1.0);
+        let qs = vec![0.0, 100.0, 1.0];
+
+        for _ in 0..1000 {
+            assert!(p.sample_legal(&qs, &[0, 2]) != 1);
+        }
+
+        let ps = p.probabilities_legal(&qs, &[0, 2]);
+
+        assert_eq!(ps[1], 0.0);
+        assert!((ps[0] - 1.0 / (1.0 + E)).abs() < 1e-7);
+        assert!((ps[2] - E / (1.0 + E)).abs() < 1e-7);
+    }
 }
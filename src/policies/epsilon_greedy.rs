@@ -1,13 +1,15 @@
-use Parameter;
-use super::{Policy, Greedy, Random};
+use {Parameter, Clock};
+use super::{Policy, FinitePolicy, sample_restricted, Greedy, Random};
 use rand::{Rng, thread_rng, ThreadRng};
 
 
+#[derive(Serialize, Deserialize)]
 pub struct EpsilonGreedy {
     greedy: Greedy,
     random: Random,
 
     epsilon: Parameter,
+    #[serde(skip, default = "thread_rng")]
     rng: ThreadRng,
 }
 
@@ -32,28 +34,52 @@ impl Policy for EpsilonGreedy {
         }
     }
 
-    fn probabilities(&mut self, qs: &[f64]) -> Vec<f64> {
-        let pr = self.epsilon / qs.len() as f64;
+    fn sample_legal(&mut self, qs: &[f64], actions: &[usize]) -> usize {
+        sample_restricted(self, qs, actions)
+    }
 
-        self.greedy
-            .probabilities(qs)
-            .iter()
-            .map(|p| pr + p * (1.0 - self.epsilon))
-            .collect()
+    fn probability(&mut self, qs: &[f64], a: &usize) -> f64 {
+        self.probabilities(qs)[*a]
+    }
+
+    fn handle_transition(&mut self) {
+        tick!(Clock::Step => self.epsilon);
+
+        self.greedy.handle_transition();
+        self.random.handle_transition();
+    }
+
+    fn handle_update(&mut self) {
+        tick!(Clock::Update => self.epsilon);
+
+        self.greedy.handle_update();
+        self.random.handle_update();
     }
 
     fn handle_terminal(&mut self) {
-        self.epsilon = self.epsilon.step();
+        tick!(Clock::Episode => self.epsilon);
 
         self.greedy.handle_terminal();
         self.random.handle_terminal();
     }
 }
 
+impl FinitePolicy for EpsilonGreedy {
+    fn probabilities(&mut self, qs: &[f64]) -> Vec<f64> {
+        let pr = self.epsilon.value() / qs.len() as f64;
+
+        self.greedy
+            .probabilities(qs)
+            .iter()
+            .map(|p| pr + p * (1.0 - self.epsilon.value()))
+            .collect()
+    }
+}
+
 
 #[cfg(test)]
 mod tests {
-    use super::{Policy, EpsilonGreedy, Parameter};
+    use super::{Policy, FinitePolicy, EpsilonGreedy, Parameter, Clock};
 
     #[test]
     fn test_sampling() {
@@ -95,7 +121,7 @@ mod tests {
     #[test]
     fn test_terminal() {
         let mut epsilon = Parameter::exponential(100.0, 1.0, 0.9);
-        let mut p = EpsilonGreedy::new(epsilon);
+        let mut p = EpsilonGreedy::new(epsilon.clone());
 
         for _ in 0..100 {
             epsilon = epsilon.step();
@@ -104,4 +130,47 @@ mod tests {
             assert_eq!(epsilon.value(), p.epsilon.value());
         }
     }
+
+    #[test]
+    fn test_transition() {
+        let mut p = EpsilonGreedy::new(Parameter::linear(1.0, 0.0, 10).with_clock(Clock::Step));
+
+        p.handle_terminal();
+        assert_eq!(p.epsilon.value(), 1.0);
+
+        for _ in 0..5 {
+            p.handle_transition();
+        }
+
+        assert_eq!(p.epsilon.value(), 0.5);
+    }
+
+    #[test]
+    fn test_update() {
+        let mut p = EpsilonGreedy::new(Parameter::linear(1.0, 0.0, 10).with_clock(Clock::Update));
+
+        p.handle_transition();
+        p.handle_terminal();
+        assert_eq!(p.epsilon.value(), 1.0);
+
+        for _ in 0..5 {
+            p.handle_update();
+        }
+
+        assert_eq!(p.epsilon.value(), 0.5);
+    }
+
+    #[test]
+    fn test_legal() {
+        let mut p = EpsilonGreedy::new(0.5);
+        let qs = vec![0.0, 1.0, 2.0, 3.0];
+
+        for _ in 0..1000 {
+            let a = p.sample_legal(&qs, &[0, 2]);
+
+            assert!(a == 0 || a == 2);
+        }
+
+        assert_eq!(p.probabilities_legal(&qs, &[0, 2]), vec![0.25, 0.0, 0.75, 0.0]);
+    }
 }
@@ -0,0 +1,171 @@
+use super::{Policy, FinitePolicy};
+
+use geometry::RegularSpace;
+use geometry::dimensions::Discrete;
+use rand::{Rng, thread_rng};
+
+
+/// Policy over a multi-discrete action space that acts independently in each
+/// dimension.
+///
+/// The input is the concatenation of the action-values of each dimension, in
+/// order, and the same underlying policy is applied to every block. The
+/// probability of a joint action is the product of its marginals.
+///
+/// Legal actions are given as a set of joint actions, since legality need not
+/// factorise across dimensions; the product distribution is then renormalised
+/// over that set.
+#[derive(Serialize, Deserialize)]
+pub struct Factored<P> {
+    policy: P,
+    n_actions: Vec<usize>,
+}
+
+impl<P: FinitePolicy> Factored<P> {
+    pub fn new(policy: P, n_actions: Vec<usize>) -> Self {
+        if n_actions.iter().any(|n| *n == 0) {
+            panic!("Each action dimension requires at least one action.");
+        }
+
+        Factored {
+            policy: policy,
+            n_actions: n_actions,
+        }
+    }
+
+    fn blocks<'a>(&self, input: &'a [f64]) -> Vec<&'a [f64]> {
+        let n_total: usize = self.n_actions.iter().sum();
+
+        if input.len() != n_total {
+            panic!("Expected {} action-values, got {}.", n_total, input.len());
+        }
+
+        let mut offset = 0;
+
+        self.n_actions.iter().map(|n| {
+            offset += *n;
+
+            &input[(offset - *n)..offset]
+        }).collect()
+    }
+}
+
+impl<P: FinitePolicy> Policy<RegularSpace<Discrete>> for Factored<P> {
+    fn sample(&mut self, input: &[f64]) -> Vec<usize> {
+        self.blocks(input).into_iter().map(|qs| self.policy.sample(qs)).collect()
+    }
+
+    fn probability(&mut self, input: &[f64], a: &Vec<usize>) -> f64 {
+        self.blocks(input).into_iter()
+            .zip(a.iter())
+            .fold(1.0, |acc, (qs, a)| acc * self.policy.probability(qs, a))
+    }
+
+    fn sample_legal(&mut self, input: &[f64], actions: &[Vec<usize>]) -> Vec<usize> {
+        if actions.is_empty() {
+            return self.sample(input);
+        }
+
+        let ps: Vec<f64> = actions.iter().map(|a| self.probability(input, a)).collect();
+        let z: f64 = ps.iter().sum();
+
+        // Deterministic marginals may all fall outside the legal set, in which
+        // case we act greedily on the summed action-values instead:
+        if z <= 0.0 {
+            let value = |a: &Vec<usize>| -> f64 {
+                self.blocks(input).iter().zip(a.iter()).map(|(qs, i)| qs[*i]).sum()
+            };
+
+            return actions.iter()
+                .fold(None, |best: Option<(f64, &Vec<usize>)>, a| {
+                    let v = value(a);
+
+                    match best {
+                        Some((bv, _)) if bv >= v => best,
+                        _ => Some((v, a)),
+                    }
+                })
+                .unwrap().1.clone();
+        }
+
+        let mut r = thread_rng().next_f64() * z;
+
+        for (a, p) in actions.iter().zip(ps.iter()) {
+            if r < *p {
+                return a.clone();
+            }
+
+            r -= *p;
+        }
+
+        actions[actions.len() - 1].clone()
+    }
+
+    fn handle_transition(&mut self) {
+        self.policy.handle_transition();
+    }
+
+    fn handle_update(&mut self) {
+        self.policy.handle_update();
+    }
+
+    fn handle_terminal(&mut self) {
+        self.policy.handle_terminal();
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{Policy, Factored};
+    use policies::{Greedy, Random};
+
+    #[test]
+    fn test_greedy() {
+        let mut p = Factored::new(Greedy, vec![2, 3]);
+
+        assert_eq!(p.sample(&[0.0, 1.0, 5.0, -1.0, 2.0]), vec![1, 0]);
+        assert_eq!(p.probability(&[0.0, 1.0, 5.0, -1.0, 2.0], &vec![1, 0]), 1.0);
+        assert_eq!(p.probability(&[0.0, 1.0, 5.0, -1.0, 2.0], &vec![0, 0]), 0.0);
+    }
+
+    #[test]
+    fn test_random() {
+        let mut p = Factored::new(Random::new(), vec![2, 4]);
+
+        let a = p.sample(&[0.0; 6]);
+
+        assert!(a[0] < 2 && a[1] < 4);
+        assert_eq!(p.probability(&[0.0; 6], &a), 0.125);
+    }
+
+    #[test]
+    fn test_legal() {
+        let mut p = Factored::new(Random::new(), vec![2, 2]);
+        let legal = vec![vec![0, 1], vec![1, 0]];
+
+        for _ in 0..100 {
+            let a = p.sample_legal(&[0.0; 4], &legal);
+
+            assert!(legal.contains(&a));
+        }
+
+        assert_eq!(p.sample_legal(&[0.0; 4], &[]).len(), 2);
+    }
+
+    #[test]
+    fn test_legal_greedy() {
+        let mut p = Factored::new(Greedy, vec![2, 3]);
+        let qs = [0.0, 1.0, 5.0, -1.0, 2.0];
+
+        // The greedy joint action [1, 0] is illegal, so the best legal one is taken:
+        assert_eq!(p.sample_legal(&qs, &[vec![0, 2], vec![1, 2], vec![0, 1]]), vec![1, 2]);
+        assert_eq!(p.sample_legal(&qs, &[vec![0, 2], vec![1, 0]]), vec![1, 0]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_wrong_input() {
+        Factored::new(Greedy, vec![2, 2]).sample(&[0.0; 3]);
+    }
+}
@@ -0,0 +1,130 @@
+use super::Policy;
+
+use {Parameter, Clock};
+use geometry::{UnitarySpace, RegularSpace};
+use geometry::dimensions::Continuous;
+use rand::{thread_rng, ThreadRng};
+use rand::distributions::{Normal, IndependentSample};
+use std::f64;
+
+
+/// Gaussian policy over continuous actions.
+///
+/// The input is interpreted as the mean of each action dimension, e.g. the
+/// output of an actor, with a standard deviation shared across dimensions.
+/// Dimensions are sampled independently.
+#[derive(Serialize, Deserialize)]
+pub struct Gaussian {
+    std_dev: Parameter,
+    #[serde(skip, default = "thread_rng")]
+    rng: ThreadRng,
+}
+
+impl Gaussian {
+    pub fn new<T: Into<Parameter>>(std_dev: T) -> Self {
+        Gaussian {
+            std_dev: std_dev.into(),
+            rng: thread_rng(),
+        }
+    }
+
+    fn draw(&mut self, mean: f64) -> f64 {
+        Normal::new(mean, self.std_dev.value()).ind_sample(&mut self.rng)
+    }
+
+    fn density(&self, mean: f64, x: f64) -> f64 {
+        let std_dev = self.std_dev.value();
+        let z = (x - mean) / std_dev;
+
+        (-0.5 * z * z).exp() / (std_dev * (2.0 * f64::consts::PI).sqrt())
+    }
+}
+
+impl Policy<UnitarySpace<Continuous>> for Gaussian {
+    fn sample(&mut self, input: &[f64]) -> f64 {
+        self.draw(input[0])
+    }
+
+    fn probability(&mut self, input: &[f64], a: &f64) -> f64 {
+        self.density(input[0], *a)
+    }
+
+    fn handle_transition(&mut self) {
+        tick!(Clock::Step => self.std_dev);
+    }
+
+    fn handle_update(&mut self) {
+        tick!(Clock::Update => self.std_dev);
+    }
+
+    fn handle_terminal(&mut self) {
+        tick!(Clock::Episode => self.std_dev);
+    }
+}
+
+impl Policy<RegularSpace<Continuous>> for Gaussian {
+    fn sample(&mut self, input: &[f64]) -> Vec<f64> {
+        input.iter().map(|m| self.draw(*m)).collect()
+    }
+
+    fn probability(&mut self, input: &[f64], a: &Vec<f64>) -> f64 {
+        input.iter().zip(a.iter()).fold(1.0, |acc, (m, x)| acc * self.density(*m, *x))
+    }
+
+    fn handle_transition(&mut self) {
+        tick!(Clock::Step => self.std_dev);
+    }
+
+    fn handle_update(&mut self) {
+        tick!(Clock::Update => self.std_dev);
+    }
+
+    fn handle_terminal(&mut self) {
+        tick!(Clock::Episode => self.std_dev);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{Policy, Gaussian};
+    use geometry::{UnitarySpace, RegularSpace};
+    use geometry::dimensions::Continuous;
+
+    type Scalar = UnitarySpace<Continuous>;
+    type Vector = RegularSpace<Continuous>;
+
+    #[test]
+    fn test_sampling() {
+        let mut p = Gaussian::new(0.5);
+
+        let xs: Vec<f64> = (0..20000).map(|_| Policy::<Scalar>::sample(&mut p, &[2.0])).collect();
+        let mean = xs.iter().sum::<f64>() / 20000.0;
+        let var = xs.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / 20000.0;
+
+        assert!((mean - 2.0).abs() < 0.02);
+        assert!((var - 0.25).abs() < 0.02);
+    }
+
+    #[test]
+    fn test_density() {
+        let mut p = Gaussian::new(1.0);
+
+        assert!((Policy::<Scalar>::probability(&mut p, &[0.0], &0.0) - 0.398942).abs() < 1e-6);
+        assert!((Policy::<Scalar>::probability(&mut p, &[1.0], &2.0) - 0.241971).abs() < 1e-6);
+
+        let joint = Policy::<Vector>::probability(&mut p, &[0.0, 1.0], &vec![0.0, 2.0]);
+        assert!((joint - 0.398942 * 0.241971).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_multidimensional() {
+        let mut p = Gaussian::new(1e-6);
+        let a = Policy::<Vector>::sample(&mut p, &[1.0, -1.0, 0.5]);
+
+        assert_eq!(a.len(), 3);
+        assert!((a[0] - 1.0).abs() < 1e-3);
+        assert!((a[1] + 1.0).abs() < 1e-3);
+        assert!((a[2] - 0.5).abs() < 1e-3);
+    }
+}
@@ -1,4 +1,4 @@
-use super::Policy;
+use super::{Policy, FinitePolicy, sample_restricted};
 
 use utils::argmaxima;
 
@@ -6,6 +6,7 @@ extern crate rand;
 use rand::Rng;
 
 
+#[derive(Serialize, Deserialize)]
 pub struct Greedy;
 
 impl Policy for Greedy {
@@ -19,6 +20,16 @@ impl Policy for Greedy {
         }
     }
 
+    fn sample_legal(&mut self, qs: &[f64], actions: &[usize]) -> usize {
+        sample_restricted(self, qs, actions)
+    }
+
+    fn probability(&mut self, qs: &[f64], a: &usize) -> f64 {
+        self.probabilities(qs)[*a]
+    }
+}
+
+impl FinitePolicy for Greedy {
     fn probabilities(&mut self, qs: &[f64]) -> Vec<f64> {
         let mut ps = vec![0.0; qs.len()];
 
@@ -36,7 +47,7 @@ impl Policy for Greedy {
 
 #[cfg(test)]
 mod tests {
-    use super::{Policy, Greedy};
+    use super::{Policy, FinitePolicy, Greedy};
 
     #[test]
     #[should_panic]
@@ -120,4 +131,15 @@ mod tests {
         assert_eq!(g.probabilities(&[1e-7, 1e-7, 1e-7, 1e-7]),
                    vec![0.25, 0.25, 0.25, 0.25]);
     }
+
+    #[test]
+    fn test_legal() {
+        let mut g = Greedy;
+        let v = vec![10.0, 1.0, 5.0, 20.0];
+
+        assert_eq!(g.sample_legal(&v, &[1, 2]), 2);
+        assert_eq!(g.sample_legal(&v, &[]), 3);
+
+        assert_eq!(g.probabilities_legal(&v, &[0, 1]), vec![1.0, 0.0, 0.0, 0.0]);
+    }
 }
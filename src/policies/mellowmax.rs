@@ -0,0 +1,234 @@
+use super::{Policy, FinitePolicy, sample_restricted};
+
+use {Parameter, Clock};
+use rand::{Rng, thread_rng, ThreadRng};
+use utils::logsumexp;
+
+
+const BETA_TOLERANCE: f64 = 1e-10;
+const MAX_ITERATIONS: usize = 200;
+const MAX_BRACKETS: usize = 64;
+
+
+/// The mellowmax operator: mm_ω(x) = log(Σ exp(ω x_i) / n) / ω.
+pub fn mellowmax(qs: &[f64], omega: f64) -> f64 {
+    (logsumexp(&qs.iter().map(|q| omega * q).collect::<Vec<f64>>()) -
+     (qs.len() as f64).ln()) / omega
+}
+
+
+/// Maximum entropy mellowmax policy.
+///
+/// Samples actions from a Boltzmann distribution whose inverse temperature β
+/// is chosen, by root-finding, such that the expected action-value equals the
+/// mellowmax of the action-values.
+///
+/// Asadi, Kavosh, and Michael L. Littman. "An alternative softmax operator for
+/// reinforcement learning." International Conference on Machine Learning.
+/// 2017.
+#[derive(Serialize, Deserialize)]
+pub struct Mellowmax {
+    omega: Parameter,
+    #[serde(skip, default = "thread_rng")]
+    rng: ThreadRng,
+}
+
+impl Mellowmax {
+    pub fn new<T: Into<Parameter>>(omega: T) -> Self {
+        Mellowmax {
+            omega: omega.into(),
+            rng: thread_rng(),
+        }
+    }
+
+    /// Solves Σ exp(β(x_i - mm)) (x_i - mm) = 0 for β.
+    pub fn beta(&self, qs: &[f64]) -> f64 {
+        let mm = mellowmax(qs, self.omega.value());
+        let advs: Vec<f64> = qs.iter().map(|q| q - mm).collect();
+
+        if advs.iter().all(|a| a.abs() < BETA_TOLERANCE) {
+            return 0.0;
+        }
+
+        // The objective is monotonically increasing in beta, so bisection is
+        // guaranteed to converge once the root is bracketed.
+        let f = |beta: f64| {
+            let m = advs.iter().fold(::std::f64::MIN, |acc, a| acc.max(beta * a));
+
+            advs.iter().fold(0.0, |acc, a| acc + (beta * a - m).exp() * a)
+        };
+
+        // Rounding in the mellowmax may leave all advantages with the same sign,
+        // in which case there is no root; the objective then tends to zero as
+        // |β| grows, so we fall back on the (anti-)greedy limit.
+        let (mut lo, mut hi) = (-1.0, 1.0);
+
+        for _ in 0..MAX_BRACKETS {
+            if f(lo) <= 0.0 {
+                break;
+            }
+
+            lo *= 2.0;
+        }
+
+        for _ in 0..MAX_BRACKETS {
+            if f(hi) >= 0.0 {
+                break;
+            }
+
+            hi *= 2.0;
+        }
+
+        if f(lo) > 0.0 {
+            return lo;
+        } else if f(hi) < 0.0 {
+            return hi;
+        }
+
+        for _ in 0..MAX_ITERATIONS {
+            let mid = (lo + hi) / 2.0;
+
+            if f(mid) < 0.0 {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+
+            if hi - lo < BETA_TOLERANCE {
+                break;
+            }
+        }
+
+        (lo + hi) / 2.0
+    }
+}
+
+impl Policy for Mellowmax {
+    fn sample(&mut self, qs: &[f64]) -> usize {
+        let ps = self.probabilities(qs);
+
+        let r = self.rng.next_f64();
+        let mut acc = 0.0;
+
+        for (i, p) in ps.iter().enumerate() {
+            acc += *p;
+
+            if acc > r {
+                return i;
+            }
+        }
+
+        ps.len() - 1
+    }
+
+    fn sample_legal(&mut self, qs: &[f64], actions: &[usize]) -> usize {
+        sample_restricted(self, qs, actions)
+    }
+
+    fn probability(&mut self, qs: &[f64], a: &usize) -> f64 {
+        self.probabilities(qs)[*a]
+    }
+
+    fn handle_transition(&mut self) {
+        tick!(Clock::Step => self.omega);
+    }
+
+    fn handle_update(&mut self) {
+        tick!(Clock::Update => self.omega);
+    }
+
+    fn handle_terminal(&mut self) {
+        tick!(Clock::Episode => self.omega);
+    }
+}
+
+impl FinitePolicy for Mellowmax {
+    fn probabilities(&mut self, qs: &[f64]) -> Vec<f64> {
+        let beta = self.beta(qs);
+        let zs: Vec<f64> = qs.iter().map(|q| beta * q).collect();
+        let z = logsumexp(&zs);
+
+        zs.iter().map(|v| (v - z).exp()).collect()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{Policy, FinitePolicy, Mellowmax, Parameter, mellowmax};
+
+    #[test]
+    #[should_panic]
+    fn test_0d() {
+        let mut p = Mellowmax::new(1.0);
+        p.sample(&vec![]);
+    }
+
+    #[test]
+    fn test_1d() {
+        let mut p = Mellowmax::new(1.0);
+
+        for i in 1..100 {
+            assert_eq!(p.sample(&vec![i as f64]), 0);
+        }
+    }
+
+    #[test]
+    fn test_operator_limits() {
+        let qs = [1.0, 2.0, 3.0];
+
+        assert!((mellowmax(&qs, 1000.0) - 3.0).abs() < 1e-2);
+        assert!((mellowmax(&qs, 1e-6) - 2.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_probabilities() {
+        let mut p = Mellowmax::new(5.0);
+        let qs = [0.5, 1.0, -0.2, 0.8];
+
+        let ps = p.probabilities(&qs);
+        let exp_q = ps.iter().zip(qs.iter()).fold(0.0, |acc, (p, q)| acc + p * q);
+
+        assert!((ps.iter().sum::<f64>() - 1.0).abs() < 1e-7);
+        assert!((exp_q - mellowmax(&qs, 5.0)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_uniform() {
+        let mut p = Mellowmax::new(5.0);
+
+        for pr in p.probabilities(&[1.0, 1.0, 1.0, 1.0]) {
+            assert!((pr - 0.25).abs() < 1e-7);
+        }
+    }
+
+    #[test]
+    fn test_unbracketed_beta() {
+        // With such a large omega, rounding places the mellowmax above every
+        // action-value, such that no root exists:
+        let mut p = Mellowmax::new(1e16);
+        let qs = [1000.0, 1000.0 + 1e-9, 1000.0 + 2e-9];
+
+        let beta = p.beta(&qs);
+        let ps = p.probabilities(&qs);
+
+        assert!(beta.is_finite());
+        assert!(ps.iter().all(|pr| pr.is_finite()));
+        assert!((ps.iter().sum::<f64>() - 1.0).abs() < 1e-7);
+        assert!(ps[2] >= ps[1] && ps[1] >= ps[0]);
+        assert!(p.sample(&qs) < 3);
+    }
+
+    #[test]
+    fn test_terminal() {
+        let mut omega = Parameter::exponential(100.0, 1.0, 0.9);
+        let mut p = Mellowmax::new(omega.clone());
+
+        for _ in 0..100 {
+            omega = omega.step();
+            p.handle_terminal();
+
+            assert_eq!(omega.value(), p.omega.value());
+        }
+    }
+}
@@ -1,11 +1,76 @@
-// TODO: Add support for generic action spaces representation.
-pub trait Policy {
-    fn sample(&mut self, qs: &[f64]) -> usize;
-    fn probabilities(&mut self, qs: &[f64]) -> Vec<f64>;
+use geometry::{Space, ActionSpace};
+
+
+/// A (possibly stochastic) mapping from some input, such as action-values or
+/// the parameters of a distribution, onto actions in the space `A`.
+///
+/// The action space defaults to a single discrete dimension, in which case the
+/// input is typically the vector of action-values.
+pub trait Policy<A: Space = ActionSpace> {
+    /// Sample an action given the policy's input.
+    fn sample(&mut self, input: &[f64]) -> A::Repr;
+
+    /// Sample an action from the set of legal `actions`; an empty set places
+    /// no restriction.
+    ///
+    /// The legal set is ignored by default, which suits policies over spaces
+    /// whose actions cannot be enumerated. Policies over finite action spaces
+    /// should restrict their distribution accordingly.
+    fn sample_legal(&mut self, input: &[f64], _actions: &[A::Repr]) -> A::Repr {
+        self.sample(input)
+    }
+
+    /// Returns the probability (or density) of taking action `a`.
+    fn probability(&mut self, input: &[f64], a: &A::Repr) -> f64;
+
+    /// Called by agents after every transition, i.e. on `Clock::Step`.
+    fn handle_transition(&mut self) {}
 
+    /// Called by agents after every learning update, i.e. on `Clock::Update`.
+    fn handle_update(&mut self) {}
+
+    /// Called by agents at the end of every episode, i.e. on `Clock::Episode`.
     fn handle_terminal(&mut self) {}
 }
 
+/// A policy over a finite set of actions whose full distribution can be
+/// enumerated.
+pub trait FinitePolicy: Policy<ActionSpace> {
+    fn probabilities(&mut self, qs: &[f64]) -> Vec<f64>;
+
+    /// Returns the distribution restricted to the set of legal `actions`, with
+    /// zero probability on all others; an empty set places no restriction.
+    fn probabilities_legal(&mut self, qs: &[f64], actions: &[usize]) -> Vec<f64> {
+        if actions.is_empty() {
+            return self.probabilities(qs);
+        }
+
+        let mut ps = vec![0.0; qs.len()];
+
+        for (a, p) in actions.iter().zip(self.probabilities(&restrict(qs, actions))) {
+            ps[*a] = p;
+        }
+
+        ps
+    }
+}
+
+
+/// Returns the values of the legal `actions` only, in the order given.
+fn restrict(qs: &[f64], actions: &[usize]) -> Vec<f64> {
+    actions.iter().map(|a| qs[*a]).collect()
+}
+
+/// Samples from `policy` as if the legal `actions` were the only ones
+/// available, mapping the result back onto the full action set.
+fn sample_restricted<P: Policy>(policy: &mut P, qs: &[f64], actions: &[usize]) -> usize {
+    if actions.is_empty() {
+        policy.sample(qs)
+    } else {
+        actions[policy.sample(&restrict(qs, actions))]
+    }
+}
+
 
 mod random;
 pub use self::random::Random;
@@ -18,3 +83,12 @@ pub use self::epsilon_greedy::EpsilonGreedy;
 
 mod boltzmann;
 pub use self::boltzmann::Boltzmann;
+
+mod mellowmax;
+pub use self::mellowmax::{Mellowmax, mellowmax};
+
+mod gaussian;
+pub use self::gaussian::Gaussian;
+
+mod factored;
+pub use self::factored::Factored;
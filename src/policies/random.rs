@@ -1,10 +1,12 @@
-use super::Policy;
+use super::{Policy, FinitePolicy, sample_restricted};
 use geometry::{Space, ActionSpace};
 use rand::{thread_rng, ThreadRng};
 use rand::distributions::{Range, IndependentSample};
 
 
+#[derive(Serialize, Deserialize)]
 pub struct Random {
+    #[serde(skip, default = "thread_rng")]
     rng: ThreadRng,
 }
 
@@ -19,6 +21,16 @@ impl Policy for Random {
         Range::new(0, qs.len()).ind_sample(&mut self.rng)
     }
 
+    fn sample_legal(&mut self, qs: &[f64], actions: &[usize]) -> usize {
+        sample_restricted(self, qs, actions)
+    }
+
+    fn probability(&mut self, qs: &[f64], _: &usize) -> f64 {
+        1.0 / qs.len() as f64
+    }
+}
+
+impl FinitePolicy for Random {
     fn probabilities(&mut self, qs: &[f64]) -> Vec<f64> {
         vec![1.0/qs.len() as f64; qs.len()]
     }
@@ -27,7 +39,7 @@ impl Policy for Random {
 
 #[cfg(test)]
 mod tests {
-    use super::{Policy, Random};
+    use super::{Policy, FinitePolicy, Random};
 
     #[test]
     fn test_sampling() {
@@ -32,6 +32,13 @@ pub fn argmaxima(vals: &[f64]) -> (f64, Vec<usize>) {
 }
 
 
+pub fn logsumexp(vals: &[f64]) -> f64 {
+    let max = vals.iter().fold(f64::MIN, |acc, v| acc.max(*v));
+
+    max + vals.iter().fold(0.0, |acc, v| acc + (v - max).exp()).ln()
+}
+
+
 // TODO: Pass by iterator so that we don't have to collect before passing.
 pub fn sub2ind(dims: &[usize], inds: &[usize]) -> usize {
     let d_it = dims.iter().rev().skip(1);
@@ -68,9 +75,47 @@ pub fn cartesian_product<T: Clone>(lists: &Vec<Vec<T>>) -> Vec<Vec<T>> {
 }
 
 
+/// Serde adaptor that stores a `HashMap` as a sequence of key-value pairs, for
+/// formats (such as JSON) that only support string keys.
+pub mod map_as_pairs {
+    use serde::{Serialize, Serializer, Deserialize, Deserializer};
+    use std::collections::HashMap;
+    use std::hash::{Hash, BuildHasher};
+
+    pub fn serialize<K, V, H, S>(map: &HashMap<K, V, H>, serializer: S) -> Result<S::Ok, S::Error>
+        where K: Serialize + Hash + Eq,
+              V: Serialize,
+              H: BuildHasher,
+              S: Serializer
+    {
+        serializer.collect_seq(map.iter())
+    }
+
+    pub fn deserialize<'de, K, V, D>(deserializer: D) -> Result<HashMap<K, V>, D::Error>
+        where K: Deserialize<'de> + Hash + Eq,
+              V: Deserialize<'de>,
+              D: Deserializer<'de>
+    {
+        let pairs: Vec<(K, V)> = Vec::deserialize(deserializer)?;
+
+        Ok(pairs.into_iter().collect())
+    }
+}
+
+
 #[cfg(test)]
 mod tests {
-    use super::{sub2ind, cartesian_product};
+    use super::{logsumexp, sub2ind, cartesian_product};
+
+    #[test]
+    fn test_logsumexp() {
+        assert!((logsumexp(&[0.0]) - 0.0).abs() < 1e-7);
+        assert!((logsumexp(&[0.0, 0.0]) - 2.0f64.ln()).abs() < 1e-7);
+        assert!((logsumexp(&[1.0, 2.0, 3.0]) - 3.40760596444438).abs() < 1e-7);
+
+        // Must not overflow for large inputs:
+        assert!((logsumexp(&[1000.0, 1000.0]) - (1000.0 + 2.0f64.ln())).abs() < 1e-7);
+    }
 
     #[test]
     fn test_sub2ind() {